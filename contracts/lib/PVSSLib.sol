@@ -15,9 +15,52 @@ library PVSSLib {
         G2Point share;
     }
 
-    function genRandomFieldElement() internal view returns (uint256) {
-        // TODO: get actual randomness from Chainlink VRF
-        return block.timestamp;
+    // NOTE: must match transcript::VERIFY_DISTRIBUTION_DOMAIN in the Rust library
+    bytes constant VERIFY_DISTRIBUTION_DOMAIN = "cassiopeia-pvss-v1/verify-distribution";
+
+    function absorbG1(bytes32 state, G1Point memory p)
+        internal
+        pure
+        returns (bytes32)
+    {
+        return keccak256(abi.encodePacked(state, p.x, p.y));
+    }
+
+    function absorbG2(bytes32 state, G2Point memory p)
+        internal
+        pure
+        returns (bytes32)
+    {
+        return keccak256(abi.encodePacked(state, p.x[0], p.x[1], p.y[0], p.y[1]));
+    }
+
+    // Fiat-Shamir challenge bound to the committee, threshold and whole ciphertext
+    function genChallenge(
+        uint256 n,
+        uint256 t,
+        G2Point[] memory pks,
+        PVSSCiphertext memory c
+    ) public pure returns (uint256) {
+        require(pks.length == n, "pks should have length n");
+        bytes32 state = keccak256(VERIFY_DISTRIBUTION_DOMAIN);
+        state = keccak256(abi.encodePacked(state, n));
+        state = keccak256(abi.encodePacked(state, t));
+        for (uint256 i = 0; i < pks.length; i++) {
+            state = absorbG2(state, pks[i]);
+        }
+        state = keccak256(abi.encodePacked(state, c.f_i.length));
+        for (uint256 i = 0; i < c.f_i.length; i++) {
+            state = absorbG1(state, c.f_i[i]);
+        }
+        state = keccak256(abi.encodePacked(state, c.a_i.length));
+        for (uint256 i = 0; i < c.a_i.length; i++) {
+            state = absorbG1(state, c.a_i[i]);
+        }
+        state = keccak256(abi.encodePacked(state, c.y_i.length));
+        for (uint256 i = 0; i < c.y_i.length; i++) {
+            state = absorbG2(state, c.y_i[i]);
+        }
+        return uint256(state) % PairingLib.GEN_ORDER;
     }
 
     function genPowersOfAlpha(uint256 alpha, uint256 t)
@@ -80,7 +123,7 @@ library PVSSLib {
         require(c.f_i.length == t, "f_i should have length t");
         require(c.a_i.length == n, "a_i should have length n");
         require(c.y_i.length == n, "y_i should have length n");
-        uint256 alpha = genRandomFieldElement();
+        uint256 alpha = genChallenge(n, t, pks, c);
         uint256[] memory lagrangeCoeffs = genAllLagrangeCoeffs(n, alpha);
         assert(lagrangeCoeffs.length == n);
         uint256[] memory powersOfAlpha = genPowersOfAlpha(alpha, t);
@@ -0,0 +1,43 @@
+use ark_bn254::Bn254;
+use ark_ec::PairingEngine;
+use cassiopeia::{committee::*, dealer::*, public::*, structs::*};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn committee(committee_pks: &[<Bn254 as PairingEngine>::G2Affine]) -> PVSSConfig<Bn254> {
+    let n = committee_pks.len();
+    PVSSConfig::new(PairingConfig::new(), committee_pks.to_vec(), n / 2 + 1).unwrap()
+}
+
+fn prepared_committee(c: &mut Criterion) {
+    let mut group = c.benchmark_group("prepared_committee");
+    group.sample_size(10);
+    for n in [100, 1000] {
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let committee_pks = (0..n)
+            .map(|_| gen_keypair(&pairing_config).1)
+            .collect::<Vec<_>>();
+        let pvss_config = committee(&committee_pks);
+        let prepared = PreparedCommittee::new(committee(&committee_pks)).unwrap();
+        let (pvss_ciphertext, _) = distribute_secret(&prepared.pvss_config).unwrap();
+
+        group.bench_function(BenchmarkId::new("prepare", n), |b| {
+            b.iter(|| PreparedCommittee::new(committee(&committee_pks)).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("distribute_secret", n), |b| {
+            b.iter(|| distribute_secret(&pvss_config).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("distribute_secret_prepared", n), |b| {
+            b.iter(|| distribute_secret_prepared(&prepared).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("verify_ciphertext", n), |b| {
+            b.iter(|| verify_ciphertext(&prepared.pvss_config, &pvss_ciphertext).unwrap())
+        });
+        group.bench_function(BenchmarkId::new("verify_ciphertext_prepared", n), |b| {
+            b.iter(|| verify_ciphertext_prepared(&prepared, &pvss_ciphertext).unwrap())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, prepared_committee);
+criterion_main!(benches);
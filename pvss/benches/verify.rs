@@ -0,0 +1,51 @@
+use ark_bn254::Bn254;
+use cassiopeia::{
+    committee::*, dealer::*, public::encryption_checks::*, structs::*, transcript::*,
+};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+
+// Run with `cargo bench --features bench --bench verify`
+fn encryption_check_miller_loops(c: &mut Criterion) {
+    let mut group = c.benchmark_group("encryption_check");
+    group.sample_size(10);
+    for n in [100, 1000] {
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let committee_pks = (0..n)
+            .map(|_| gen_keypair(&pairing_config).1)
+            .collect::<Vec<_>>();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, n / 2 + 1).unwrap();
+        let (pvss_ciphertext, _) = distribute_secret(&pvss_config).unwrap();
+        let alpha = verify_distribution_challenge(&pvss_config, &pvss_ciphertext);
+        let pairing_config = &pvss_config.pairing_config;
+        let slot_pks = pvss_config.slot_pks();
+
+        group.bench_function(BenchmarkId::new("n_plus_1_pairings", n), |b| {
+            b.iter(|| {
+                verify_encryptions_n_plus_1(
+                    pairing_config,
+                    &pvss_ciphertext.a_i,
+                    &pvss_ciphertext.y_i,
+                    &slot_pks,
+                    alpha,
+                )
+                .unwrap()
+            })
+        });
+        group.bench_function(BenchmarkId::new("2n_pairings", n), |b| {
+            b.iter(|| {
+                verify_encryptions_2n(
+                    pairing_config,
+                    &pvss_ciphertext.a_i,
+                    &pvss_ciphertext.y_i,
+                    &slot_pks,
+                    alpha,
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, encryption_check_miller_loops);
+criterion_main!(benches);
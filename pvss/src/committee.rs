@@ -1,18 +1,132 @@
-use crate::{errors::*, structs::*};
+use crate::{
+    engine::PVSSEngine,
+    errors::*,
+    structs::*,
+    transcript::{partial_decryption_challenge, possession_challenge},
+};
 
-use ark_bn254::{Fr, G2Affine};
-use ark_ec::{AffineCurve, ProjectiveCurve};
-use ark_ff::{Field, PrimeField};
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{Field, PrimeField, UniformRand};
+use rand::{thread_rng, CryptoRng, RngCore};
+
+pub fn gen_keypair<E: PairingEngine>(pairing_config: &PairingConfig<E>) -> (E::Fr, E::G2Affine) {
+    gen_keypair_with_rng(pairing_config, &mut thread_rng())
+}
+
+pub fn gen_keypair_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pairing_config: &PairingConfig<E>,
+    rng: &mut R,
+) -> (E::Fr, E::G2Affine) {
+    let sk = E::Fr::rand(rng);
+    let pk = pairing_config.h.mul(sk.into_repr()).into_affine();
+    (sk, pk)
+}
+
+/// Proves knowledge of `sk` so the key can be registered in a committee
+pub fn prove_possession<E: PVSSEngine>(
+    pairing_config: &PairingConfig<E>,
+    sk: &E::Fr,
+    pk: &E::G2Affine,
+) -> ProofOfPossession<E> {
+    prove_possession_with_rng(pairing_config, sk, pk, &mut thread_rng())
+}
+
+pub fn prove_possession_with_rng<E: PVSSEngine, R: RngCore + CryptoRng>(
+    pairing_config: &PairingConfig<E>,
+    sk: &E::Fr,
+    pk: &E::G2Affine,
+    rng: &mut R,
+) -> ProofOfPossession<E> {
+    let r = E::Fr::rand(rng);
+    let c = possession_challenge::<E>(pk, &pairing_config.h.mul(r.into_repr()).into_affine());
+    ProofOfPossession { c, z: r + c * sk }
+}
 
 // Verify share in here as well
-pub fn decrypt_share(
-    pvss_ciphertext: &PVSSCiphertext,
-    sk: &Fr,
+pub fn decrypt_share<E: PairingEngine>(
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    sk: &E::Fr,
     i: usize,
-) -> Result<G2Affine, PVSSError> {
+) -> Result<E::G2Affine, PVSSError> {
     let sk_inverse = sk.inverse().ok_or(PVSSError::InvalidSecretKeyError)?;
-    let decrypted_share = pvss_ciphertext.y_i[i]
+    let decrypted_share = pvss_ciphertext
+        .y_i
+        .get(i)
+        .ok_or(PVSSError::InvalidParticipantId(i))?
         .mul(sk_inverse.into_repr())
         .into_affine();
     Ok(decrypted_share)
 }
+
+/// Publishes `e(u, S_i)` for the KEM ciphertext `kem_ciphertext` without revealing `S_i`
+pub fn partial_decrypt<E: PVSSEngine>(
+    pairing_config: &PairingConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    kem_ciphertext: &KemCiphertext<E>,
+    share: &E::G2Affine,
+    i: usize,
+) -> Result<PartialDecryption<E>, PVSSError> {
+    partial_decrypt_with_rng(
+        pairing_config,
+        pvss_ciphertext,
+        kem_ciphertext,
+        share,
+        i,
+        &mut thread_rng(),
+    )
+}
+
+pub fn partial_decrypt_with_rng<E: PVSSEngine, R: RngCore + CryptoRng>(
+    pairing_config: &PairingConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    kem_ciphertext: &KemCiphertext<E>,
+    share: &E::G2Affine,
+    i: usize,
+    rng: &mut R,
+) -> Result<PartialDecryption<E>, PVSSError> {
+    let a_i = pvss_ciphertext
+        .a_i
+        .get(i)
+        .ok_or(PVSSError::InvalidParticipantId(i))?;
+    let u = kem_ciphertext.u;
+    let d_i = E::pairing(u, *share);
+
+    // Schnorr-style proof of knowledge of S_i under S -> (e(g, S), e(u, S))
+    let w = pairing_config.h.mul(E::Fr::rand(rng).into_repr());
+    let t_1 = E::pairing(pairing_config.g, w);
+    let t_2 = E::pairing(u, w);
+    let c = partial_decryption_challenge::<E>(i, a_i, &u, &d_i, &t_1, &t_2);
+    let z = (w + share.mul(c.into_repr())).into_affine();
+    Ok(PartialDecryption { i, d_i, c, z })
+}
+
+/// Decrypts every slot held by `member`, as `(slot, share)` pairs ready for reconstruction
+pub fn decrypt_member_shares<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    sk: &E::Fr,
+    member: usize,
+) -> Result<Vec<(usize, E::G2Affine)>, PVSSError> {
+    pvss_config
+        .slots(member)
+        .ok_or(PVSSError::InvalidParticipantId(member))?
+        .map(|slot| Ok((slot, decrypt_share(pvss_ciphertext, sk, slot)?)))
+        .collect()
+}
+
+/// Decrypts every LSSS row owned by `member`, as `(row, share)` pairs
+pub fn decrypt_member_rows<E: PairingEngine>(
+    lsss_config: &LsssConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    sk: &E::Fr,
+    member: usize,
+) -> Result<Vec<(usize, E::G2Affine)>, PVSSError> {
+    lsss_config
+        .lsss
+        .row_members
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| **m == member)
+        .map(|(row, _)| Ok((row, decrypt_share(pvss_ciphertext, sk, row)?)))
+        .collect()
+}
@@ -2,78 +2,349 @@ use std::iter;
 
 use crate::{errors::*, structs::*};
 
-use ark_ec::{AffineCurve, ProjectiveCurve};
-use ark_ff::{One, PrimeField, UniformRand, Zero};
-use rand::thread_rng;
+use ark_ec::{AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::{BigInteger, Field, One, PrimeField, UniformRand, Zero};
+use rand::{thread_rng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
 
-use ark_bn254::{Fr, G2Affine};
 use num_bigint::RandBigInt;
 
-pub fn distribute_secret(
-    pvss_config: &PVSSConfig,
-) -> Result<(PVSSCiphertext, PVSSSecrets), PVSSError> {
-    let mut rng = thread_rng();
+// Secret needs to be <= 250 bits for circom compatibility
+pub const MAX_SECRET_BITS: u32 = 250;
 
-    // Secret needs to be <= 250 bits for circom compatibility
-    let f_0 = Fr::from(rng.gen_biguint(250));
-    let f = iter::once(f_0).chain((1..pvss_config.t)
-        .map(|_| Fr::rand(&mut rng)))
+pub fn distribute_secret<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    distribute_secret_with_rng(pvss_config, &mut thread_rng())
+}
+
+pub fn distribute_secret_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    let f_0 = E::Fr::from(rng.gen_biguint(MAX_SECRET_BITS as u64));
+    distribute_given_secret_with_rng(pvss_config, f_0, rng)
+}
+
+/// Deals a zero-secret polynomial that members add onto a long-lived dealing
+/// with `refresh_ciphertext`, re-randomizing every share but not `h^{f_0}`
+pub fn distribute_refresh<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+) -> Result<PVSSCiphertext<E>, PVSSError> {
+    distribute_refresh_with_rng(pvss_config, &mut thread_rng())
+}
+
+pub fn distribute_refresh_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    rng: &mut R,
+) -> Result<PVSSCiphertext<E>, PVSSError> {
+    let (refresh, _) = distribute_given_secret_with_rng(pvss_config, E::Fr::zero(), rng)?;
+    Ok(refresh)
+}
+
+/// Deals a caller-chosen `f_0`, e.g. to escrow an existing key with the committee
+pub fn distribute_given_secret<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    secret: E::Fr,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    distribute_given_secret_with_rng(pvss_config, secret, &mut thread_rng())
+}
+
+pub fn distribute_given_secret_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    f_0: E::Fr,
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    pvss_config.validate()?;
+    let bits = f_0.into_repr().num_bits();
+    if bits > MAX_SECRET_BITS {
+        return Err(PVSSError::SecretTooLarge {
+            bits,
+            max: MAX_SECRET_BITS,
+        });
+    }
+    let f = iter::once(f_0)
+        .chain((1..pvss_config.t).map(|_| E::Fr::rand(rng)))
         .collect::<Vec<_>>();
 
-    let y_eval_i = (1..=pvss_config.committee_pks.len())
+    let pvss_ciphertext = encrypt_polynomial(pvss_config, &f, None)?;
+
+    let h_f_0 = pvss_config
+        .pairing_config
+        .h
+        .mul(f[0].into_repr())
+        .into_affine();
+
+    let pvss_secrets = PVSSSecrets { f_0: f[0], h_f_0 };
+
+    Ok((pvss_ciphertext, pvss_secrets))
+}
+
+// Commits to the coefficients f and encrypts p(1), ..., p(n) to the slot keys,
+// through `g_table` when the caller has one
+fn encrypt_polynomial<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    f: &[E::Fr],
+    g_table: Option<&FixedBaseTable<E::G1Projective>>,
+) -> Result<PVSSCiphertext<E>, PVSSError> {
+    let y_eval_i = evaluate_polynomial(f, pvss_config.num_slots());
+
+    let commit = |scalars: &[E::Fr]| match g_table {
+        Some(g_table) => g_table.mul(scalars),
+        None => scalars
+            .iter()
+            .map(|a| {
+                pvss_config
+                    .pairing_config
+                    .g
+                    .mul(a.into_repr())
+                    .into_affine()
+            })
+            .collect::<Vec<_>>(),
+    };
+    // NOTE: includes secret f[0] itself
+    let f_i = commit(f);
+    let a_i = commit(&y_eval_i);
+
+    // NOTE: a table per slot key would hold n * 2^window G2 points, so y_i stays plain
+    let slot_pks = pvss_config.slot_pks();
+    let y_i = y_eval_i
+        .iter()
+        .enumerate()
+        .map::<Result<E::G2Projective, PVSSError>, _>(|(i, a)| {
+            Ok(slot_pks
+                .get(i)
+                .ok_or(PVSSError::InvalidParticipantId(i))?
+                .mul(a.into_repr()))
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+    let y_i = E::G2Projective::batch_normalization_into_affine(&y_i);
+
+    Ok(PVSSCiphertext { f_i, a_i, y_i })
+}
+
+// p(1), ..., p(n) for coefficients f
+fn evaluate_polynomial<F: PrimeField>(f: &[F], n: usize) -> Vec<F> {
+    (1..=n)
         .map(|i| {
-            let x = Fr::from(i as i64);
+            let x = F::from(i as u64);
             f.iter()
-                .scan(Fr::one(), |x_pow, &c| {
+                .scan(F::one(), |x_pow, &c| {
                     let prev_x_pow = *x_pow;
                     *x_pow *= x;
                     Some(c * prev_x_pow)
                 })
-                .fold(Fr::zero(), |acc, x| acc + x)
+                .fold(F::zero(), |acc, x| acc + x)
+        })
+        .collect()
+}
+
+/// Deals `count` independent random secrets to the same committee, sharing a
+/// fixed-base table for g and spreading the dealings over the rayon thread pool
+pub fn distribute_secrets_batch<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    count: usize,
+) -> Result<Vec<Dealing<E>>, PVSSError> {
+    distribute_secrets_batch_with_rng(pvss_config, count, &mut thread_rng())
+}
+
+pub fn distribute_secrets_batch_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    count: usize,
+    rng: &mut R,
+) -> Result<Vec<Dealing<E>>, PVSSError> {
+    pvss_config.validate()?;
+    let n = pvss_config.num_slots();
+    let t = pvss_config.t;
+    // NOTE: one seed per dealing keeps the output independent of thread scheduling
+    let seeds = (0..count)
+        .map(|_| {
+            let mut seed = <ChaCha20Rng as SeedableRng>::Seed::default();
+            rng.fill_bytes(&mut seed);
+            seed
         })
         .collect::<Vec<_>>();
 
-    // NOTE: includes secret f[0] itself
-    let f_i = f
-        .iter()
-        .map(|a| {
-            pvss_config
+    let g_table = FixedBaseTable::new(pvss_config.pairing_config.g, count * (t + n));
+
+    seeds
+        .into_par_iter()
+        .map(|seed| {
+            let mut rng = ChaCha20Rng::from_seed(seed);
+            let f_0 = E::Fr::from(rng.gen_biguint(MAX_SECRET_BITS as u64));
+            let f = iter::once(f_0)
+                .chain((1..t).map(|_| E::Fr::rand(&mut rng)))
+                .collect::<Vec<_>>();
+            let pvss_ciphertext = encrypt_polynomial(pvss_config, &f, Some(&g_table))?;
+            let h_f_0 = pvss_config
                 .pairing_config
-                .g
-                .mul(a.into_repr())
-                .into_affine()
+                .h
+                .mul(f_0.into_repr())
+                .into_affine();
+            Ok((pvss_ciphertext, PVSSSecrets { f_0, h_f_0 }))
         })
+        .collect()
+}
+
+/// Like `distribute_secret`, with the fixed-base table of `prepared`
+pub fn distribute_secret_prepared<E: PairingEngine>(
+    prepared: &PreparedCommittee<E>,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    distribute_secret_prepared_with_rng(prepared, &mut thread_rng())
+}
+
+pub fn distribute_secret_prepared_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    prepared: &PreparedCommittee<E>,
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    let pvss_config = &prepared.pvss_config;
+    pvss_config.validate()?;
+    let f_0 = E::Fr::from(rng.gen_biguint(MAX_SECRET_BITS as u64));
+    let f = iter::once(f_0)
+        .chain((1..pvss_config.t).map(|_| E::Fr::rand(rng)))
+        .collect::<Vec<_>>();
+    let pvss_ciphertext = encrypt_polynomial(pvss_config, &f, Some(&prepared.g_table))?;
+    let h_f_0 = pvss_config
+        .pairing_config
+        .h
+        .mul(f_0.into_repr())
+        .into_affine();
+    Ok((pvss_ciphertext, PVSSSecrets { f_0, h_f_0 }))
+}
+
+/// Deals a random secret to `lsss_config`: one `f_i` per LSSS column, one share per row
+pub fn distribute_secret_lsss<E: PairingEngine>(
+    lsss_config: &LsssConfig<E>,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    distribute_secret_lsss_with_rng(lsss_config, &mut thread_rng())
+}
+
+pub fn distribute_secret_lsss_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    lsss_config: &LsssConfig<E>,
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, PVSSSecrets<E>), PVSSError> {
+    lsss_config.validate()?;
+    let g = lsss_config.pairing_config.g;
+    let f_0 = E::Fr::from(rng.gen_biguint(MAX_SECRET_BITS as u64));
+    let rho = iter::once(f_0)
+        .chain((1..lsss_config.lsss.num_cols()).map(|_| E::Fr::rand(rng)))
         .collect::<Vec<_>>();
+    let shares = lsss_config.lsss.shares(&rho);
 
-    let a_i = y_eval_i
+    let f_i = rho
         .iter()
-        .map(|a| {
-            pvss_config
-                .pairing_config
-                .g
-                .mul(a.into_repr())
-                .into_affine()
-        })
+        .map(|r| g.mul(r.into_repr()).into_affine())
+        .collect::<Vec<_>>();
+    let a_i = shares
+        .iter()
+        .map(|s| g.mul(s.into_repr()).into_affine())
+        .collect::<Vec<_>>();
+    let y_i = shares
+        .iter()
+        .zip(lsss_config.row_pks().iter())
+        .map(|(s, pk)| pk.mul(s.into_repr()).into_affine())
         .collect::<Vec<_>>();
 
-    let y_i = y_eval_i
+    let h_f_0 = lsss_config
+        .pairing_config
+        .h
+        .mul(f_0.into_repr())
+        .into_affine();
+    Ok((PVSSCiphertext { f_i, a_i, y_i }, PVSSSecrets { f_0, h_f_0 }))
+}
+
+/// Deals `packed_config.k` random secrets in a single ciphertext
+pub fn distribute_packed_secrets<E: PairingEngine>(
+    packed_config: &PackedPVSSConfig<E>,
+) -> Result<(PVSSCiphertext<E>, PackedPVSSSecrets<E>), PVSSError> {
+    distribute_packed_secrets_with_rng(packed_config, &mut thread_rng())
+}
+
+pub fn distribute_packed_secrets_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    packed_config: &PackedPVSSConfig<E>,
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, PackedPVSSSecrets<E>), PVSSError> {
+    let secrets = (0..packed_config.k)
+        .map(|_| E::Fr::from(rng.gen_biguint(MAX_SECRET_BITS as u64)))
+        .collect::<Vec<_>>();
+    distribute_given_packed_secrets_with_rng(packed_config, &secrets, rng)
+}
+
+pub fn distribute_given_packed_secrets<E: PairingEngine>(
+    packed_config: &PackedPVSSConfig<E>,
+    secrets: &[E::Fr],
+) -> Result<(PVSSCiphertext<E>, PackedPVSSSecrets<E>), PVSSError> {
+    distribute_given_packed_secrets_with_rng(packed_config, secrets, &mut thread_rng())
+}
+
+pub fn distribute_given_packed_secrets_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    packed_config: &PackedPVSSConfig<E>,
+    secrets: &[E::Fr],
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, PackedPVSSSecrets<E>), PVSSError> {
+    packed_config.validate()?;
+    let k = packed_config.k;
+    if secrets.len() != k {
+        return Err(PVSSError::InvalidPacking(secrets.len()));
+    }
+    if let Some(bits) = secrets
         .iter()
-        .enumerate()
-        .map::<Result<G2Affine, PVSSError>, _>(|(i, a)| {
-            Ok(pvss_config
-                .committee_pks
-                .get(i)
-                .ok_or(PVSSError::InvalidParticipantId(i))?
-                .mul(a.into_repr())
-                .into_affine())
-        })
-        .collect::<Result<_, _>>()?;
+        .map(|s| s.into_repr().num_bits())
+        .find(|bits| *bits > MAX_SECRET_BITS)
+    {
+        return Err(PVSSError::SecretTooLarge {
+            bits,
+            max: MAX_SECRET_BITS,
+        });
+    }
 
-    let pvss_ciphertext = PVSSCiphertext { f_i, a_i, y_i };
+    // p(x) = L(x) + Z(x) r(x), with L interpolating secrets[j] at x = -j,
+    // Z(x) = (x + 0)(x + 1)...(x + k - 1) and r random of degree t - 1
+    let mut f = vec![E::Fr::zero(); packed_config.pvss_config.t];
+    for (j, s) in secrets.iter().enumerate() {
+        let mut basis = vec![E::Fr::one()];
+        let mut denominator = E::Fr::one();
+        for m in (0..k).filter(|m| *m != j) {
+            basis = poly_mul(&basis, &[E::Fr::from(m as u64), E::Fr::one()]);
+            denominator *= E::Fr::from(m as u64) - E::Fr::from(j as u64);
+        }
+        let scale = *s * denominator.inverse().ok_or(PVSSError::InvalidPacking(k))?;
+        for (c, b) in f.iter_mut().zip(basis.iter()) {
+            *c += scale * b;
+        }
+    }
+    let z = (0..k).fold(vec![E::Fr::one()], |z, m| {
+        poly_mul(&z, &[E::Fr::from(m as u64), E::Fr::one()])
+    });
+    let r = (0..packed_config.pvss_config.t - k)
+        .map(|_| E::Fr::rand(rng))
+        .collect::<Vec<_>>();
+    for (c, zr) in f.iter_mut().zip(poly_mul(&z, &r)) {
+        *c += zr;
+    }
 
-    let h_f_0 = pvss_config.pairing_config.h.mul(f[0]).into_affine();
+    let pvss_ciphertext = encrypt_polynomial(&packed_config.pvss_config, &f, None)?;
 
-    let pvss_secrets = PVSSSecrets { f_0: f[0], h_f_0 };
+    let h = packed_config.pvss_config.pairing_config.h;
+    let h_secrets = secrets
+        .iter()
+        .map(|s| h.mul(s.into_repr()).into_affine())
+        .collect();
+    let packed_secrets = PackedPVSSSecrets {
+        secrets: secrets.to_vec(),
+        h_secrets,
+    };
 
-    Ok((pvss_ciphertext, pvss_secrets))
+    Ok((pvss_ciphertext, packed_secrets))
+}
+
+fn poly_mul<F: PrimeField>(a: &[F], b: &[F]) -> Vec<F> {
+    let mut product = vec![F::zero(); a.len() + b.len() - 1];
+    for (i, x) in a.iter().enumerate() {
+        for (j, y) in b.iter().enumerate() {
+            product[i + j] += *x * y;
+        }
+    }
+    product
 }
@@ -0,0 +1,214 @@
+use std::collections::HashSet;
+
+use crate::{
+    dealer::{distribute_given_secret_with_rng, MAX_SECRET_BITS},
+    engine::PVSSEngine,
+    errors::*,
+    public::{sum_ciphertexts, verify_ciphertext},
+    structs::*,
+    transcript::{ciphertext_digest, contribution_challenge},
+};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{PrimeField, UniformRand, Zero};
+use num_bigint::RandBigInt;
+use rand::{thread_rng, CryptoRng, RngCore};
+
+/// Deals a random secret as committee member `dealer` of a DKG, together with a
+/// signature of knowledge of its `f_0` and of `sk`. The dealer must forget `f_0`.
+/// `session_id` must be unique to this DKG run.
+pub fn deal_contribution<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    dealer: usize,
+    sk: &E::Fr,
+) -> Result<(PVSSCiphertext<E>, ContributionProof<E>), PVSSError> {
+    deal_contribution_with_rng(pvss_config, session_id, dealer, sk, &mut thread_rng())
+}
+
+pub fn deal_contribution_with_rng<E: PVSSEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    dealer: usize,
+    sk: &E::Fr,
+    rng: &mut R,
+) -> Result<(PVSSCiphertext<E>, ContributionProof<E>), PVSSError> {
+    let pairing_config = &pvss_config.pairing_config;
+    let pk = *pvss_config
+        .committee_pks
+        .get(dealer)
+        .ok_or(PVSSError::InvalidParticipantId(dealer))?;
+    if pairing_config.h.mul(sk.into_repr()).into_affine() != pk {
+        return Err(PVSSError::InvalidSecretKeyError);
+    }
+    let f_0 = E::Fr::from(rng.gen_biguint(contribution_bits(pvss_config) as u64));
+    let (ciphertext, secrets) = distribute_given_secret_with_rng(pvss_config, f_0, rng)?;
+
+    let r = E::Fr::rand(rng);
+    let r_sk = E::Fr::rand(rng);
+    // NOTE: the challenge does not read c, z or z_sk
+    let mut proof = ContributionProof {
+        dealer,
+        pk,
+        ciphertext_hash: ciphertext_digest(&ciphertext),
+        commitment: ciphertext.f_i[0],
+        c: E::Fr::zero(),
+        z: E::Fr::zero(),
+        z_sk: E::Fr::zero(),
+    };
+    let c = contribution_challenge(
+        pvss_config,
+        session_id,
+        &proof,
+        &pairing_config.g.mul(r.into_repr()).into_affine(),
+        &pairing_config.h.mul(r_sk.into_repr()).into_affine(),
+    );
+    proof.c = c;
+    proof.z = r + c * secrets.f_0;
+    proof.z_sk = r_sk + c * sk;
+    Ok((ciphertext, proof))
+}
+
+// NOTE: n contributions below 2^{250 - ceil(log2 n)} sum to at most 250 bits. This
+// cannot be checked on the commitments, see verify_aggregated_ciphertext.
+fn contribution_bits<E: PVSSEngine>(pvss_config: &PVSSConfig<E>) -> u32 {
+    let n = pvss_config.committee_pks.len().max(1);
+    MAX_SECRET_BITS - (usize::BITS - (n - 1).leading_zeros())
+}
+
+// Checks that `pk` is the dealer's committee key and that g^z / commitment^c and
+// h^{z_sk} / pk^c hash back to c in this session
+fn verify_contribution_proof<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    proof: &ContributionProof<E>,
+) -> Result<(), PVSSError> {
+    if pvss_config.committee_pks.get(proof.dealer) != Some(&proof.pk) {
+        return Err(PVSSError::InvalidContribution(proof.dealer));
+    }
+    let pairing_config = &pvss_config.pairing_config;
+    let r = pairing_config.g.mul(proof.z.into_repr()) - proof.commitment.mul(proof.c.into_repr());
+    let r_sk = pairing_config.h.mul(proof.z_sk.into_repr()) - proof.pk.mul(proof.c.into_repr());
+    let c = contribution_challenge(
+        pvss_config,
+        session_id,
+        proof,
+        &r.into_affine(),
+        &r_sk.into_affine(),
+    );
+    if c != proof.c {
+        return Err(PVSSError::InvalidContribution(proof.dealer));
+    }
+    Ok(())
+}
+
+pub fn verify_contribution<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    ciphertext: &PVSSCiphertext<E>,
+    proof: &ContributionProof<E>,
+) -> Result<(), PVSSError> {
+    if ciphertext.f_i.first() != Some(&proof.commitment)
+        || ciphertext_digest(ciphertext) != proof.ciphertext_hash
+    {
+        return Err(PVSSError::InvalidContribution(proof.dealer));
+    }
+    verify_contribution_proof(pvss_config, session_id, proof)?;
+    verify_ciphertext(pvss_config, ciphertext)
+}
+
+// Assumes every contribution has already been verified
+pub fn aggregate_ciphertexts<E: PVSSEngine>(
+    contributions: &[(PVSSCiphertext<E>, ContributionProof<E>)],
+) -> Result<AggregatedCiphertext<E>, PVSSError> {
+    let ((first, _), rest) = contributions
+        .split_first()
+        .ok_or(PVSSError::NotEnoughContributions { have: 0, need: 1 })?;
+    let mut dealers = HashSet::new();
+    for (_, proof) in contributions.iter() {
+        if !dealers.insert(proof.dealer) {
+            return Err(PVSSError::DuplicateDealer(proof.dealer));
+        }
+    }
+    Ok(AggregatedCiphertext {
+        ciphertext: sum_ciphertexts(first, rest.iter().map(|(ciphertext, _)| ciphertext))?,
+        proofs: contributions
+            .iter()
+            .map(|(_, proof)| proof.clone())
+            .collect(),
+    })
+}
+
+/// Checks that the aggregate is a valid dealing whose `f_0` is the sum of proven
+/// contributions to `session_id` from at least t distinct committee keys.
+///
+/// Passing does not mean the aggregated `f_0` fits in `MAX_SECRET_BITS`: nothing
+/// bounds a single contribution, so one dishonest dealer can make the secret
+/// unusable in the circuit. Only rely on the bound if every dealer is trusted to
+/// have used `deal_contribution`.
+pub fn verify_aggregated_ciphertext<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    aggregated: &AggregatedCiphertext<E>,
+) -> Result<(), PVSSError> {
+    let mut dealers = HashSet::new();
+    let mut pks = HashSet::new();
+    for proof in aggregated.proofs.iter() {
+        if !dealers.insert(proof.dealer) {
+            return Err(PVSSError::DuplicateDealer(proof.dealer));
+        }
+        verify_contribution_proof(pvss_config, session_id, proof)?;
+        pks.insert(proof.pk);
+    }
+    // NOTE: at most t - 1 committee members are assumed corrupt, and each key
+    // proves its sk, so t distinct keys include an honest dealer
+    if pks.len() < pvss_config.t {
+        return Err(PVSSError::NotEnoughContributions {
+            have: pks.len(),
+            need: pvss_config.t,
+        });
+    }
+    let commitment = aggregated
+        .proofs
+        .iter()
+        .fold(E::G1Projective::zero(), |acc, proof| {
+            acc + proof.commitment.into_projective()
+        });
+    if aggregated.ciphertext.f_i.first() != Some(&commitment.into_affine()) {
+        return Err(PVSSError::AggregateMismatch);
+    }
+    verify_ciphertext(pvss_config, &aggregated.ciphertext)
+}
+
+/// Final DKG step run by anyone: drops invalid contributions and aggregates
+/// the rest. Returns the aggregate and the rejected dealers, or `DuplicateDealer`
+/// if a dealer made two valid contributions. The aggregated `f_0` carries the
+/// same caveat as in `verify_aggregated_ciphertext`.
+pub fn complete_dkg<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    contributions: Vec<(PVSSCiphertext<E>, ContributionProof<E>)>,
+) -> Result<(AggregatedCiphertext<E>, Vec<usize>), PVSSError> {
+    let (valid, invalid): (Vec<_>, Vec<_>) =
+        contributions.into_iter().partition(|(ciphertext, proof)| {
+            verify_contribution(pvss_config, session_id, ciphertext, proof).is_ok()
+        });
+    let mut dealers = HashSet::new();
+    for (_, proof) in valid.iter() {
+        if !dealers.insert(proof.dealer) {
+            return Err(PVSSError::DuplicateDealer(proof.dealer));
+        }
+    }
+    let pks = valid
+        .iter()
+        .map(|(_, proof)| proof.pk)
+        .collect::<HashSet<_>>();
+    if pks.len() < pvss_config.t {
+        return Err(PVSSError::NotEnoughContributions {
+            have: pks.len(),
+            need: pvss_config.t,
+        });
+    }
+    let rejected = invalid.iter().map(|(_, proof)| proof.dealer).collect();
+    Ok((aggregate_ciphertexts(&valid)?, rejected))
+}
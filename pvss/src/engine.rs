@@ -0,0 +1,95 @@
+use ark_ec::{
+    bls12::{Bls12, Bls12Parameters},
+    bn::{Bn, BnParameters},
+    PairingEngine,
+};
+use ark_ff::{Fp2, Zero};
+
+use crate::errors::PVSSError;
+
+/// Pairing engines whose G1 coordinates live in `Fq` and G2 coordinates in `Fq2`,
+/// which is what the JSON codec and the Fiat-Shamir transcript need to see.
+/// The point at infinity is encoded as (0, 0), as in EIP-196/197.
+pub trait PVSSEngine: PairingEngine {
+    /// Returns `[c0, c1]`
+    fn fqe_to_components(x: &Self::Fqe) -> [Self::Fq; 2];
+    fn fqe_from_components(c: [Self::Fq; 2]) -> Self::Fqe;
+    fn g1_coordinates(p: &Self::G1Affine) -> (Self::Fq, Self::Fq);
+    /// Rejects points that are off the curve or outside the prime-order subgroup
+    fn g1_from_coordinates(x: Self::Fq, y: Self::Fq) -> Result<Self::G1Affine, PVSSError>;
+    fn g1_from_coordinates_unchecked(x: Self::Fq, y: Self::Fq) -> Self::G1Affine;
+    fn g2_coordinates(p: &Self::G2Affine) -> (Self::Fqe, Self::Fqe);
+    /// Rejects points that are off the curve or outside the prime-order subgroup
+    fn g2_from_coordinates(x: Self::Fqe, y: Self::Fqe) -> Result<Self::G2Affine, PVSSError>;
+    fn g2_from_coordinates_unchecked(x: Self::Fqe, y: Self::Fqe) -> Self::G2Affine;
+}
+
+macro_rules! impl_pvss_engine {
+    ($engine:ident, $params:ident) => {
+        impl<P: $params> PVSSEngine for $engine<P> {
+            fn fqe_to_components(x: &Self::Fqe) -> [Self::Fq; 2] {
+                [x.c0, x.c1]
+            }
+
+            fn fqe_from_components(c: [Self::Fq; 2]) -> Self::Fqe {
+                Fp2::<P::Fp2Params>::new(c[0], c[1])
+            }
+
+            fn g1_coordinates(p: &Self::G1Affine) -> (Self::Fq, Self::Fq) {
+                if p.infinity {
+                    return (Self::Fq::zero(), Self::Fq::zero());
+                }
+                (p.x, p.y)
+            }
+
+            fn g1_from_coordinates(x: Self::Fq, y: Self::Fq) -> Result<Self::G1Affine, PVSSError> {
+                let p = Self::g1_from_coordinates_unchecked(x, y);
+                if !p.is_on_curve() {
+                    return Err(PVSSError::PointNotOnCurve);
+                }
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    return Err(PVSSError::PointNotInSubgroup);
+                }
+                Ok(p)
+            }
+
+            fn g1_from_coordinates_unchecked(x: Self::Fq, y: Self::Fq) -> Self::G1Affine {
+                if x.is_zero() && y.is_zero() {
+                    return Self::G1Affine::zero();
+                }
+                Self::G1Affine::new(x, y, false)
+            }
+
+            fn g2_coordinates(p: &Self::G2Affine) -> (Self::Fqe, Self::Fqe) {
+                if p.infinity {
+                    return (Self::Fqe::zero(), Self::Fqe::zero());
+                }
+                (p.x, p.y)
+            }
+
+            fn g2_from_coordinates(
+                x: Self::Fqe,
+                y: Self::Fqe,
+            ) -> Result<Self::G2Affine, PVSSError> {
+                let p = Self::g2_from_coordinates_unchecked(x, y);
+                if !p.is_on_curve() {
+                    return Err(PVSSError::PointNotOnCurve);
+                }
+                if !p.is_in_correct_subgroup_assuming_on_curve() {
+                    return Err(PVSSError::PointNotInSubgroup);
+                }
+                Ok(p)
+            }
+
+            fn g2_from_coordinates_unchecked(x: Self::Fqe, y: Self::Fqe) -> Self::G2Affine {
+                if x.is_zero() && y.is_zero() {
+                    return Self::G2Affine::zero();
+                }
+                Self::G2Affine::new(x, y, false)
+            }
+        }
+    };
+}
+
+impl_pvss_engine!(Bn, BnParameters);
+impl_pvss_engine!(Bls12, Bls12Parameters);
@@ -1,4 +1,3 @@
-use ark_bn254::G1Affine;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -6,9 +5,81 @@ pub enum PVSSError {
     #[error("Ratio incorrect")]
     RatioIncorrect,
     #[error("Evaluations are wrong: product = {0}")]
-    EvaluationsCheckError(G1Affine),
-    #[error("Could not generate evaluation domain")]
+    EvaluationsCheckError(String),
+    #[error("Invalid participant id {0}")]
     InvalidParticipantId(usize),
+    #[error("Invalid threshold t = {t} for a committee of n = {n}")]
+    InvalidThreshold { t: usize, n: usize },
+    #[error("Ciphertext {field} has length {actual}, expected {expected}")]
+    InvalidCiphertextShape {
+        field: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("Got {shares} shares but {indices} indices")]
+    ShareCountMismatch { shares: usize, indices: usize },
     #[error("Invalid secret key error")]
     InvalidSecretKeyError,
+    #[error("Invalid hex string {0:?}")]
+    InvalidHex(String),
+    #[error("Hex value {0} is not a canonical field element")]
+    FieldElementTooLarge(String),
+    #[error("Point is not on the curve")]
+    PointNotOnCurve,
+    #[error("Point is not in the prime-order subgroup")]
+    PointNotInSubgroup,
+    #[error("Not enough valid shares: have {have}, need {need}")]
+    NotEnoughShares { have: usize, need: usize },
+    #[error("Could not seal payload")]
+    SealError,
+    #[error("Could not open envelope: wrong secret or tampered envelope")]
+    OpenError,
+    #[error("KEM ciphertext is not addressed to this dealing")]
+    CommitmentMismatch,
+    #[error("Invalid partial decryption from participant {0}")]
+    InvalidPartialDecryption(usize),
+    #[error("Secret {0} is not below the scalar field modulus")]
+    SecretOutOfRange(String),
+    #[error("Secret has {bits} bits, but the circuit supports at most {max}")]
+    SecretTooLarge { bits: u32, max: u32 },
+    #[error("Ciphertexts have different shapes")]
+    CiphertextShapeMismatch,
+    #[error("Refresh does not deal a zero secret")]
+    RefreshSecretNotZero,
+    #[error("Refreshed ciphertext does not match the refresh transcript")]
+    RefreshMismatch,
+    #[error("Invalid contribution from dealer {0}")]
+    InvalidContribution(usize),
+    #[error("Dealer {0} contributed more than once")]
+    DuplicateDealer(usize),
+    #[error("Not enough valid contributions: have {have}, need {need}")]
+    NotEnoughContributions { have: usize, need: usize },
+    #[error("Aggregated f_0 commitment does not match the contributions")]
+    AggregateMismatch,
+    #[error("Invalid or missing proof of possession for committee member {0}")]
+    InvalidProofOfPossession(usize),
+    #[error("Got {pops} proofs of possession for {members} members")]
+    PopCountMismatch { pops: usize, members: usize },
+    #[error("Share index {0} appears more than once")]
+    DuplicateIndex(usize),
+    #[error("Reconstructed secret does not match the dealing")]
+    SecretMismatch,
+    #[error("Got {weights} weights for {members} members")]
+    WeightCountMismatch { weights: usize, members: usize },
+    #[error("Member {0} must have a positive weight")]
+    InvalidWeight(usize),
+    #[error("Invalid policy: {0}")]
+    PolicyParseError(String),
+    #[error("Invalid LSSS: {0}")]
+    InvalidLsss(String),
+    #[error("Shares do not satisfy the access policy")]
+    Unauthorized,
+    #[error("Invalid hierarchy: {0}")]
+    InvalidHierarchy(String),
+    #[error("Cannot pack {0} secrets into one polynomial")]
+    InvalidPacking(usize),
+    #[error("Packed secret index {j} out of range for {k} secrets")]
+    InvalidSecretIndex { j: usize, k: usize },
+    #[error("Cannot encrypt to the identity commitment")]
+    IdentityCommitment,
 }
@@ -0,0 +1,84 @@
+use crate::{
+    engine::PVSSEngine,
+    errors::*,
+    seal::{decrypt_payload, encrypt_payload},
+    structs::*,
+    transcript::Transcript,
+};
+
+use ark_ec::{AffineCurve, ProjectiveCurve};
+use ark_ff::{to_bytes, PrimeField, UniformRand, Zero};
+use chacha20poly1305::Key;
+use hkdf::Hkdf;
+use rand::{thread_rng, CryptoRng, RngCore};
+use sha2::Sha256;
+
+pub const KEM_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/kem";
+
+// HKDF-SHA256 over e(F_0, h)^r = e(g^r, h^{f_0}), salted with a hash of (F_0, g^r)
+fn derive_key<E: PVSSEngine>(
+    shared: &E::Fqk,
+    commitment: &E::G1Affine,
+    u: &E::G1Affine,
+) -> Result<Key, PVSSError> {
+    let mut transcript = Transcript::new(KEM_DOMAIN);
+    transcript.append_g1::<E>(commitment);
+    transcript.append_g1::<E>(u);
+    let ikm = to_bytes!(shared).map_err(|_| PVSSError::SealError)?;
+    let mut key = Key::default();
+    Hkdf::<Sha256>::new(Some(&transcript.digest()), &ikm)
+        .expand(KEM_DOMAIN, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    Ok(key)
+}
+
+/// Encrypts `payload` to the public commitment `f_i[0] = g^{f_0}` of a dealing.
+/// Only holders of `h^{f_0}` (i.e. t members of the committee) can decrypt.
+pub fn kem_encrypt<E: PVSSEngine>(
+    pairing_config: &PairingConfig<E>,
+    commitment: &E::G1Affine,
+    payload: &[u8],
+) -> Result<KemCiphertext<E>, PVSSError> {
+    kem_encrypt_with_rng(pairing_config, commitment, payload, &mut thread_rng())
+}
+
+pub fn kem_encrypt_with_rng<E: PVSSEngine, R: RngCore + CryptoRng>(
+    pairing_config: &PairingConfig<E>,
+    commitment: &E::G1Affine,
+    payload: &[u8],
+    rng: &mut R,
+) -> Result<KemCiphertext<E>, PVSSError> {
+    // NOTE: e(1, h)^r = 1 would make the key public, e.g. for a refresh dealing
+    if commitment.is_zero() {
+        return Err(PVSSError::IdentityCommitment);
+    }
+    let r = E::Fr::rand(rng);
+    let u = pairing_config.g.mul(r.into_repr()).into_affine();
+    let shared = E::pairing(commitment.mul(r.into_repr()), pairing_config.h);
+    let key = derive_key::<E>(&shared, commitment, &u)?;
+    let (nonce, payload) = encrypt_payload(&key, &[], payload, rng)?;
+    Ok(KemCiphertext {
+        commitment: *commitment,
+        u,
+        nonce,
+        payload,
+    })
+}
+
+/// Decrypts with the secret recovered by `combine_shares` on the dealing
+/// whose `f_i[0]` equals `ciphertext.commitment`
+pub fn kem_decrypt<E: PVSSEngine>(
+    h_f_0: &E::G2Affine,
+    ciphertext: &KemCiphertext<E>,
+) -> Result<Vec<u8>, PVSSError> {
+    kem_decrypt_shared(&E::pairing(ciphertext.u, *h_f_0), ciphertext)
+}
+
+/// Decrypts with `e(u, h^{f_0})` as returned by `combine_partial_decryptions`
+pub fn kem_decrypt_shared<E: PVSSEngine>(
+    shared: &E::Fqk,
+    ciphertext: &KemCiphertext<E>,
+) -> Result<Vec<u8>, PVSSError> {
+    let key = derive_key::<E>(shared, &ciphertext.commitment, &ciphertext.u)?;
+    decrypt_payload(&key, &[], &ciphertext.nonce, &ciphertext.payload)
+}
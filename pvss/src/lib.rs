@@ -1,7 +1,13 @@
 pub mod committee;
 pub mod dealer;
+pub mod dkg;
+pub mod engine;
 pub mod errors;
+pub mod kem;
+pub mod lsss;
 pub mod public;
+pub mod seal;
 pub mod serialize;
 pub mod structs;
 pub mod tests;
+pub mod transcript;
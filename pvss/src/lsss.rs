@@ -0,0 +1,385 @@
+use crate::errors::PVSSError;
+
+use ark_ff::PrimeField;
+
+/// Deepest nesting of threshold gates accepted, so recursion stays bounded
+pub const MAX_POLICY_DEPTH: usize = 64;
+
+fn check_depth(depth: usize) -> Result<(), PVSSError> {
+    if depth > MAX_POLICY_DEPTH {
+        return Err(PVSSError::PolicyParseError(format!(
+            "policy is nested deeper than {MAX_POLICY_DEPTH}"
+        )));
+    }
+    Ok(())
+}
+
+/// Monotone access policy over committee members (0-based indices into `committee_pks`).
+/// AND and OR are the n-of-n and 1-of-n thresholds.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Policy {
+    Member(usize),
+    Threshold { k: usize, children: Vec<Policy> },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Token {
+    Number(usize),
+    And,
+    Or,
+    Of,
+    Open,
+    Close,
+    Comma,
+}
+
+fn tokenize(input: &str) -> Result<Vec<Token>, PVSSError> {
+    let mut tokens = vec![];
+    let mut chars = input.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        match c {
+            _ if c.is_whitespace() => {
+                chars.next();
+            }
+            '(' | ')' | ',' => {
+                chars.next();
+                tokens.push(match c {
+                    '(' => Token::Open,
+                    ')' => Token::Close,
+                    _ => Token::Comma,
+                });
+            }
+            _ if c.is_ascii_alphanumeric() => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek().filter(|c| c.is_ascii_alphanumeric()) {
+                    word.push(c);
+                    chars.next();
+                }
+                tokens.push(match word.to_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "of" => Token::Of,
+                    _ => Token::Number(word.parse().map_err(|_| {
+                        PVSSError::PolicyParseError(format!("unexpected word {word:?}"))
+                    })?),
+                });
+            }
+            _ => {
+                return Err(PVSSError::PolicyParseError(format!(
+                    "unexpected character {c:?}"
+                )))
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+// expr := and ("or" and)*
+// and  := atom ("and" atom)*
+// atom := member | k "of" "(" expr ("," expr)* ")" | "(" expr ")"
+struct Parser {
+    tokens: Vec<Token>,
+    position: usize,
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn expect(&mut self, token: Token) -> Result<(), PVSSError> {
+        match self.peek() {
+            Some(t) if *t == token => {
+                self.position += 1;
+                Ok(())
+            }
+            t => Err(PVSSError::PolicyParseError(format!(
+                "expected {token:?}, found {t:?}"
+            ))),
+        }
+    }
+
+    fn gate(&mut self, gate: Token) -> Result<Policy, PVSSError> {
+        let sub = |p: &mut Self| match gate {
+            Token::Or => p.and(),
+            _ => p.atom(),
+        };
+        let mut children = vec![sub(self)?];
+        while self.peek() == Some(&gate) {
+            self.position += 1;
+            children.push(sub(self)?);
+        }
+        Ok(match (gate, children.len()) {
+            (_, 1) => children.pop().unwrap(),
+            (Token::Or, _) => Policy::Threshold { k: 1, children },
+            _ => Policy::Threshold {
+                k: children.len(),
+                children,
+            },
+        })
+    }
+
+    fn expr(&mut self) -> Result<Policy, PVSSError> {
+        self.gate(Token::Or)
+    }
+
+    fn and(&mut self) -> Result<Policy, PVSSError> {
+        self.gate(Token::And)
+    }
+
+    fn atom(&mut self) -> Result<Policy, PVSSError> {
+        self.depth += 1;
+        check_depth(self.depth)?;
+        let atom = self.nested_atom();
+        self.depth -= 1;
+        atom
+    }
+
+    fn nested_atom(&mut self) -> Result<Policy, PVSSError> {
+        match self.peek().cloned() {
+            Some(Token::Number(x)) => {
+                self.position += 1;
+                if self.peek() != Some(&Token::Of) {
+                    return Ok(Policy::Member(x));
+                }
+                self.position += 1;
+                self.expect(Token::Open)?;
+                let mut children = vec![self.expr()?];
+                while self.peek() == Some(&Token::Comma) {
+                    self.position += 1;
+                    children.push(self.expr()?);
+                }
+                self.expect(Token::Close)?;
+                Ok(Policy::Threshold { k: x, children })
+            }
+            Some(Token::Open) => {
+                self.position += 1;
+                let policy = self.expr()?;
+                self.expect(Token::Close)?;
+                Ok(policy)
+            }
+            t => Err(PVSSError::PolicyParseError(format!(
+                "expected a member or a threshold, found {t:?}"
+            ))),
+        }
+    }
+}
+
+impl Policy {
+    /// Parses e.g. `2 of (0, 1, 2) and (3 or 4)`
+    pub fn parse(input: &str) -> Result<Self, PVSSError> {
+        let mut parser = Parser {
+            tokens: tokenize(input)?,
+            position: 0,
+            depth: 0,
+        };
+        let policy = parser.expr()?;
+        if let Some(t) = parser.peek() {
+            return Err(PVSSError::PolicyParseError(format!(
+                "unexpected trailing {t:?}"
+            )));
+        }
+        Ok(policy)
+    }
+}
+
+/// Monotone span program: a set of rows is authorized iff their span contains (1, 0, ..., 0).
+/// Row i is owned by member `row_members[i]` and gets share <matrix[i], rho> with rho[0] the secret.
+pub struct Lsss<F: PrimeField> {
+    pub matrix: Vec<Vec<F>>,
+    pub row_members: Vec<usize>,
+}
+
+impl<F: PrimeField> Lsss<F> {
+    /// Compiles threshold gates by inserting Vandermonde columns: child j of a
+    /// k-of-m gate with vector v gets v || (j, j^2, ..., j^{k-1})
+    pub fn from_policy(policy: &Policy) -> Result<Self, PVSSError> {
+        fn insert<F: PrimeField>(
+            policy: &Policy,
+            depth: usize,
+            vector: Vec<F>,
+            num_cols: &mut usize,
+            lsss: &mut Lsss<F>,
+        ) -> Result<(), PVSSError> {
+            check_depth(depth)?;
+            match policy {
+                Policy::Member(member) => {
+                    lsss.matrix.push(vector);
+                    lsss.row_members.push(*member);
+                }
+                Policy::Threshold { k, children } => {
+                    if *k == 0 || *k > children.len() {
+                        return Err(PVSSError::InvalidThreshold {
+                            t: *k,
+                            n: children.len(),
+                        });
+                    }
+                    let base = *num_cols;
+                    *num_cols += k - 1;
+                    for (j, child) in children.iter().enumerate() {
+                        let x = F::from((j + 1) as u64);
+                        let mut child_vector = vector.clone();
+                        child_vector.resize(base, F::zero());
+                        child_vector.extend((1..*k).scan(F::one(), |x_pow, _| {
+                            *x_pow *= x;
+                            Some(*x_pow)
+                        }));
+                        insert(child, depth + 1, child_vector, num_cols, lsss)?;
+                    }
+                }
+            }
+            Ok(())
+        }
+
+        let mut lsss = Lsss {
+            matrix: vec![],
+            row_members: vec![],
+        };
+        let mut num_cols = 1;
+        insert(policy, 1, vec![F::one()], &mut num_cols, &mut lsss)?;
+        for row in lsss.matrix.iter_mut() {
+            row.resize(num_cols, F::zero());
+        }
+        Ok(lsss)
+    }
+
+    /// Tassa's hierarchical threshold scheme as an LSSS: a member of tier l gets
+    /// the derivative of order `thresholds[l - 1]` (0 for tier 0) of a polynomial
+    /// of degree `thresholds.last() - 1`. A set is authorized when, for every l,
+    /// it has at least `thresholds[l]` members from tiers 0..=l.
+    pub fn hierarchical(tiers: &[usize], thresholds: &[usize]) -> Result<Self, PVSSError> {
+        if thresholds.is_empty()
+            || thresholds[0] == 0
+            || thresholds.windows(2).any(|w| w[0] >= w[1])
+        {
+            return Err(PVSSError::InvalidHierarchy(format!(
+                "thresholds {thresholds:?} must be positive and increasing"
+            )));
+        }
+        if let Some(member) = tiers.iter().position(|tier| *tier >= thresholds.len()) {
+            return Err(PVSSError::InvalidHierarchy(format!(
+                "member {member} is in tier {} of {}",
+                tiers[member],
+                thresholds.len()
+            )));
+        }
+        let t = thresholds[thresholds.len() - 1];
+
+        // NOTE: points must grow with the tier for Tassa's non-singularity bound
+        let mut order = (0..tiers.len()).collect::<Vec<_>>();
+        order.sort_by_key(|member| tiers[*member]);
+        let mut matrix = vec![vec![]; tiers.len()];
+        for (rank, member) in order.into_iter().enumerate() {
+            let x = F::from((rank + 1) as u64);
+            let derivative = match tiers[member] {
+                0 => 0,
+                tier => thresholds[tier - 1],
+            };
+            // d^k/dx^k x^j = j! / (j - k)! x^{j - k}
+            matrix[member] = (0..t)
+                .map(|j| {
+                    if j < derivative {
+                        return F::zero();
+                    }
+                    let falling_factorial = (j - derivative + 1..=j)
+                        .map(|i| F::from(i as u64))
+                        .product::<F>();
+                    falling_factorial * x.pow([(j - derivative) as u64])
+                })
+                .collect();
+        }
+        Ok(Lsss {
+            matrix,
+            row_members: (0..tiers.len()).collect(),
+        })
+    }
+
+    /// Checks that every row has the same width and exactly one owner
+    pub fn validate(&self) -> Result<(), PVSSError> {
+        if self.row_members.len() != self.matrix.len() {
+            return Err(PVSSError::InvalidLsss(format!(
+                "got {} row members for {} rows",
+                self.row_members.len(),
+                self.matrix.len()
+            )));
+        }
+        let num_cols = self.num_cols();
+        if let Some(row) = self.matrix.iter().position(|row| row.len() != num_cols) {
+            return Err(PVSSError::InvalidLsss(format!(
+                "row {row} has {} columns, expected {num_cols}",
+                self.matrix[row].len()
+            )));
+        }
+        Ok(())
+    }
+
+    pub fn num_rows(&self) -> usize {
+        self.matrix.len()
+    }
+
+    pub fn num_cols(&self) -> usize {
+        self.matrix.first().map_or(0, |row| row.len())
+    }
+
+    pub fn shares(&self, rho: &[F]) -> Vec<F> {
+        self.matrix
+            .iter()
+            .map(|row| row.iter().zip(rho.iter()).map(|(m, r)| *m * r).sum())
+            .collect()
+    }
+
+    /// Finds w with sum_i w_i matrix[rows[i]] = (1, 0, ..., 0) by Gaussian
+    /// elimination. Returns None if `rows` is not authorized.
+    pub fn reconstruction_coefficients(&self, rows: &[usize]) -> Option<Vec<F>> {
+        let num_cols = self.num_cols();
+        // Augmented system: one equation per column, one unknown per row
+        let mut system = (0..num_cols)
+            .map(|c| {
+                let mut equation = rows
+                    .iter()
+                    .map(|r| self.matrix.get(*r).and_then(|row| row.get(c)).copied())
+                    .collect::<Option<Vec<_>>>()?;
+                equation.push(if c == 0 { F::one() } else { F::zero() });
+                Some(equation)
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        let mut pivots = vec![];
+        let mut pivot_row = 0;
+        for col in 0..rows.len() {
+            let Some(found) = (pivot_row..num_cols).find(|e| !system[*e][col].is_zero()) else {
+                continue;
+            };
+            system.swap(pivot_row, found);
+            let inverse = system[pivot_row][col].inverse()?;
+            for x in system[pivot_row].iter_mut() {
+                *x *= inverse;
+            }
+            let pivot = system[pivot_row].clone();
+            for (e, equation) in system.iter_mut().enumerate() {
+                if e != pivot_row && !equation[col].is_zero() {
+                    let factor = equation[col];
+                    for (x, p) in equation.iter_mut().zip(pivot.iter()) {
+                        *x -= factor * p;
+                    }
+                }
+            }
+            pivots.push(col);
+            pivot_row += 1;
+        }
+        // Inconsistent if a zero equation has a non-zero right-hand side
+        if system[pivot_row..]
+            .iter()
+            .any(|equation| !equation[rows.len()].is_zero())
+        {
+            return None;
+        }
+
+        let mut coefficients = vec![F::zero(); rows.len()];
+        for (e, col) in pivots.iter().enumerate() {
+            coefficients[*col] = system[e][rows.len()];
+        }
+        Some(coefficients)
+    }
+}
@@ -1,34 +1,70 @@
-use ark_bn254::{Fr, G2Affine};
-use ark_ec::{AffineCurve, ProjectiveCurve};
-use ark_ff::{PrimeField, UniformRand};
-use clap::{Parser, Subcommand};
-use rand::thread_rng;
+use ark_bn254::Bn254;
+use clap::{Parser, Subcommand, ValueEnum};
+use num_bigint::BigUint;
+use rand::{thread_rng, CryptoRng, RngCore};
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
-use std::{error::Error, io};
+use std::{
+    error::Error,
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+};
 
 use cassiopeia::{
-    committee::decrypt_share,
-    dealer::distribute_secret,
-    public::{combine_shares, verify_ciphertext},
+    committee::{decrypt_share, gen_keypair_with_rng, prove_possession_with_rng},
+    dealer::{distribute_given_secret, distribute_secret},
+    engine::PVSSEngine,
+    errors::PVSSError,
+    kem::{kem_decrypt, kem_encrypt},
+    public::{
+        combine_shares, verify_ciphertext, verify_committee_pops, verify_secret,
+        verify_secret_scalar,
+    },
+    seal::{open, seal},
     serialize::*,
-    structs::{PVSSCiphertext, PVSSConfig, PVSSSecrets, PairingConfig},
+    structs::{
+        KemCiphertext, PVSSCiphertext, PVSSConfig, PVSSSecrets, PairingConfig, ProofOfPossession,
+        SealedEnvelope,
+    },
 };
 
 #[derive(Parser, Debug)]
 #[command(author, about, long_about = None)]
 struct Cli {
+    /// Pairing-friendly curve to run the PVSS over
+    #[arg(long, value_enum, global = true, default_value_t = Curve::Bn254)]
+    curve: Curve,
     #[command(subcommand)]
     command: Commands,
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Eq, ValueEnum)]
+enum Curve {
+    Bn254,
+    #[cfg(feature = "bls12_381")]
+    #[value(name = "bls12-381")]
+    Bls12_381,
+}
+
 #[derive(Subcommand, Debug)]
 enum Commands {
-    /// Generates n secret key and public key pairs
+    /// Generates n secret key and public key pairs with proofs of possession
     #[command(arg_required_else_help = true)]
     GenKeys { n: usize },
+    /// Verifies the proofs of possession of committee public keys
+    #[command()]
+    VerifyPops,
     /// Generates PVSS ciphertext and secrets for threshold t
     #[command(arg_required_else_help = true)]
-    DealSecret { t: usize },
+    DealSecret {
+        t: usize,
+        /// Deal the Fr hex string in this file instead of a random secret
+        #[arg(long, conflicts_with = "secret_stdin")]
+        secret_file: Option<PathBuf>,
+        /// Deal the Fr hex string on the line after the public keys
+        #[arg(long)]
+        secret_stdin: bool,
+    },
     /// Decrypts a share at a specified index in the ciphertext with a secret key
     #[command()]
     DecryptShare,
@@ -38,58 +74,122 @@ enum Commands {
     /// Verifies PVSS ciphertext
     #[command()]
     VerifyCiphertext,
+    /// Verifies a claimed secret h^f_0 (or revealed scalar f_0) against a dealing
+    #[command()]
+    VerifySecret,
+    /// Encrypts the contents of a file under the secret of a dealing
+    #[command(arg_required_else_help = true)]
+    Seal { payload: PathBuf },
+    /// Decrypts a sealed envelope with a combined secret and writes the payload to stdout
+    #[command()]
+    Open,
+    /// Encrypts the contents of a file to the public commitment f_i[0] of a dealing
+    #[command(arg_required_else_help = true)]
+    KemEncrypt { payload: PathBuf },
+    /// Decrypts a KEM ciphertext with a combined secret and writes the payload to stdout
+    #[command()]
+    KemDecrypt,
 }
 
 #[derive(Serialize)]
-struct GenKeysOutput {
-    sks: Vec<FrSerializable>,
-    pks: Vec<G2AffineSerializable>,
+#[serde(bound = "E: PVSSEngine")]
+struct GenKeysOutput<E: PVSSEngine> {
+    sks: Vec<FrSerializable<E>>,
+    pks: Vec<G2AffineSerializable<E>>,
+    pops: Vec<ProofOfPossession<E>>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "E: PVSSEngine")]
+struct VerifyPopsInput<E: PVSSEngine> {
+    pks: Vec<G2AffineSerializable<E>>,
+    pops: Vec<ProofOfPossession<E>>,
 }
 
 #[derive(Serialize)]
-struct DealSecretOutput {
-    ciphertext: PVSSCiphertext,
-    secrets: PVSSSecrets,
+#[serde(bound = "E: PVSSEngine")]
+struct DealSecretOutput<E: PVSSEngine> {
+    ciphertext: PVSSCiphertext<E>,
+    secrets: PVSSSecrets<E>,
 }
 
 #[derive(Deserialize)]
-struct DecryptShareInput {
+#[serde(bound = "E: PVSSEngine")]
+struct DecryptShareInput<E: PVSSEngine> {
     i: usize,
-    ciphertext: PVSSCiphertext,
-    sk: FrSerializable,
+    ciphertext: PVSSCiphertext<E>,
+    sk: FrSerializable<E>,
 }
 
 #[derive(Deserialize)]
-struct CombineSharesInputElem {
+#[serde(bound = "E: PVSSEngine")]
+struct CombineSharesInputElem<E: PVSSEngine> {
     i: usize,
-    share: G2AffineSerializable,
+    share: G2AffineSerializable<E>,
 }
 
 #[derive(Deserialize)]
-struct VerifyCiphertextInput {
+#[serde(bound = "E: PVSSEngine")]
+struct VerifyCiphertextInput<E: PVSSEngine> {
     t: usize,
-    pks: Vec<G2AffineSerializable>,
-    ciphertext: PVSSCiphertext,
+    pks: Vec<G2AffineSerializable<E>>,
+    ciphertext: PVSSCiphertext<E>,
 }
 
-fn gen_keys(pairing_config: &PairingConfig, n: usize) -> GenKeysOutput {
-    let mut rng = thread_rng();
-    let sks = (0..n).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
-    let pks = sks
-        .iter()
-        .map(|sk| pairing_config.h.mul(sk.into_repr()).into_affine())
-        .collect::<Vec<_>>();
-    let sks_serializable = sks
-        .iter()
-        .map(|sk: &Fr| (*sk).into())
-        .collect::<Vec<FrSerializable>>();
-    let pks_serializable = pks
+#[derive(Deserialize)]
+#[serde(bound = "E: PVSSEngine")]
+struct VerifySecretInput<E: PVSSEngine> {
+    t: usize,
+    pks: Vec<G2AffineSerializable<E>>,
+    ciphertext: PVSSCiphertext<E>,
+    h_f_0: Option<G2AffineSerializable<E>>,
+    f_0: Option<FrSerializable<E>>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "E: PVSSEngine")]
+struct SealInput<E: PVSSEngine> {
+    ciphertext: PVSSCiphertext<E>,
+    h_f_0: G2AffineSerializable<E>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "E: PVSSEngine")]
+struct OpenInput<E: PVSSEngine> {
+    h_f_0: G2AffineSerializable<E>,
+    envelope: SealedEnvelope,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "E: PVSSEngine")]
+struct KemEncryptInput<E: PVSSEngine> {
+    commitment: G1AffineSerializable<E>,
+}
+
+#[derive(Deserialize)]
+#[serde(bound = "E: PVSSEngine")]
+struct KemDecryptInput<E: PVSSEngine> {
+    h_f_0: G2AffineSerializable<E>,
+    ciphertext: KemCiphertext<E>,
+}
+
+fn gen_keys<E: PVSSEngine, R: RngCore + CryptoRng>(
+    pairing_config: &PairingConfig<E>,
+    n: usize,
+    rng: &mut R,
+) -> GenKeysOutput<E> {
+    let (sks, pks): (Vec<_>, Vec<_>) = (0..n)
+        .map(|_| gen_keypair_with_rng(pairing_config, rng))
+        .unzip();
+    let pops = sks
         .iter()
-        .map(|pk: &G2Affine| (*pk).into())
-        .collect::<Vec<G2AffineSerializable>>();
+        .zip(pks.iter())
+        .map(|(sk, pk)| prove_possession_with_rng(pairing_config, sk, pk, rng))
+        .collect();
     GenKeysOutput {
-        sks: sks_serializable,
-        pks: pks_serializable,
+        sks: sks.into_iter().map(FrSerializable).collect(),
+        pks: pks.into_iter().map(G2AffineSerializable).collect(),
+        pops,
     }
 }
 
@@ -97,33 +197,65 @@ fn read_obj<T: DeserializeOwned>() -> Result<T, io::Error> {
     let mut raw = String::new();
     io::stdin().read_line(&mut raw)?;
     let deserializable: T = serde_json::from_str(raw.as_str())?;
-    Ok(deserializable.into())
+    Ok(deserializable)
 }
 
-fn deserialize_vec<T: DeserializeOwned, S>(a: Vec<T>) -> Vec<S>
-where
-    T: Into<S>,
-{
-    a.into_iter().map(|elem: T| elem.into()).collect::<Vec<S>>()
+// Accepts 0x-prefixed hex of any length and case, with or without the surrounding
+// JSON quotes. The bit bound is left to distribute_given_secret.
+fn parse_secret<E: PVSSEngine>(raw: &str) -> Result<E::Fr, PVSSError> {
+    let raw = raw.trim().trim_matches('"');
+    let value = raw
+        .strip_prefix("0x")
+        .or_else(|| raw.strip_prefix("0X"))
+        .and_then(|digits| BigUint::parse_bytes(digits.as_bytes(), 16))
+        .ok_or_else(|| PVSSError::InvalidHex(raw.to_string()))?;
+    let secret = E::Fr::from(value.clone());
+    if Into::<BigUint>::into(secret) != value {
+        return Err(PVSSError::SecretOutOfRange(raw.to_string()));
+    }
+    Ok(secret)
 }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    // TODO: fix error handling
-    let args = Cli::parse();
-    let pairing_config = PairingConfig::new();
+fn deserialize_pks<E: PVSSEngine>(pks: Vec<G2AffineSerializable<E>>) -> Vec<E::G2Affine> {
+    pks.into_iter().map(|pk| pk.0).collect()
+}
+
+fn run<E: PVSSEngine>(command: Commands) -> Result<(), Box<dyn Error>> {
+    let pairing_config = PairingConfig::<E>::new();
 
-    match args.command {
+    match command {
         Commands::GenKeys { n } => {
-            let all_keys = gen_keys(&pairing_config, n);
+            let all_keys = gen_keys(&pairing_config, n, &mut thread_rng());
             println!("{}", serde_json::to_string(&all_keys)?);
             Ok(())
         }
-        Commands::DealSecret { t } => {
-            let pks = deserialize_vec::<G2AffineSerializable, G2Affine>(read_obj::<
-                Vec<G2AffineSerializable>,
-            >()?);
-            let pvss_config = PVSSConfig::new(pairing_config, pks, t);
-            let (ciphertext, secrets) = distribute_secret(&pvss_config).unwrap();
+        Commands::VerifyPops => {
+            let input = read_obj::<VerifyPopsInput<E>>()?;
+            let pks = deserialize_pks(input.pks);
+            verify_committee_pops(&pairing_config, &pks, &input.pops)?;
+            println!("true");
+            Ok(())
+        }
+        Commands::DealSecret {
+            t,
+            secret_file,
+            secret_stdin,
+        } => {
+            let pks = deserialize_pks(read_obj::<Vec<G2AffineSerializable<E>>>()?);
+            let pvss_config = PVSSConfig::new(pairing_config, pks, t)?;
+            let secret = if let Some(path) = secret_file {
+                Some(parse_secret::<E>(&fs::read_to_string(path)?)?)
+            } else if secret_stdin {
+                let mut raw = String::new();
+                io::stdin().read_line(&mut raw)?;
+                Some(parse_secret::<E>(&raw)?)
+            } else {
+                None
+            };
+            let (ciphertext, secrets) = match secret {
+                Some(secret) => distribute_given_secret(&pvss_config, secret)?,
+                None => distribute_secret(&pvss_config)?,
+            };
             let output = DealSecretOutput {
                 ciphertext,
                 secrets,
@@ -132,34 +264,83 @@ fn main() -> Result<(), Box<dyn Error>> {
             Ok(())
         }
         Commands::DecryptShare => {
-            let input = read_obj::<DecryptShareInput>()?;
-            let decrypted_share: G2AffineSerializable =
-                decrypt_share(&input.ciphertext, &input.sk.into(), input.i)?.into();
+            let input = read_obj::<DecryptShareInput<E>>()?;
+            let decrypted_share =
+                G2AffineSerializable::<E>(decrypt_share(&input.ciphertext, &input.sk.0, input.i)?);
             println!("{}", serde_json::to_string(&decrypted_share)?);
             Ok(())
         }
         Commands::CombineShares => {
-            let input = read_obj::<Vec<CombineSharesInputElem>>()?;
-            let (indices, decrypted_shares): (Vec<usize>, Vec<G2Affine>) = input
-                .iter()
-                .map(|elem| (elem.i, elem.share.into()))
-                .unzip::<usize, G2Affine, Vec<usize>, Vec<G2Affine>>();
-            let result: G2AffineSerializable = combine_shares(&decrypted_shares, &indices)?.into();
+            let input = read_obj::<Vec<CombineSharesInputElem<E>>>()?;
+            let (indices, decrypted_shares): (Vec<usize>, Vec<E::G2Affine>) =
+                input.iter().map(|elem| (elem.i, elem.share.0)).unzip();
+            let result = G2AffineSerializable::<E>(combine_shares(&decrypted_shares, &indices)?);
             println!("{}", serde_json::to_string(&result)?);
             Ok(())
         }
         Commands::VerifyCiphertext => {
-            let input = read_obj::<VerifyCiphertextInput>()?;
-            let pks = deserialize_vec::<G2AffineSerializable, G2Affine>(input.pks);
+            let input = read_obj::<VerifyCiphertextInput<E>>()?;
+            let pks = deserialize_pks(input.pks);
             println!(
                 "{}",
                 verify_ciphertext(
-                    &PVSSConfig::new(pairing_config, pks, input.t),
+                    &PVSSConfig::new(pairing_config, pks, input.t)?,
                     &input.ciphertext
                 )
                 .is_ok()
             );
             Ok(())
         }
+        Commands::VerifySecret => {
+            let input = read_obj::<VerifySecretInput<E>>()?;
+            if input.h_f_0.is_none() && input.f_0.is_none() {
+                return Err("expected h_f_0 or f_0".into());
+            }
+            let pvss_config = PVSSConfig::new(pairing_config, deserialize_pks(input.pks), input.t)?;
+            let h_f_0_ok = input.h_f_0.is_none_or(|h_f_0| {
+                verify_secret(&pvss_config, &input.ciphertext, &h_f_0.0).is_ok()
+            });
+            let f_0_ok = input.f_0.is_none_or(|f_0| {
+                verify_secret_scalar(&pvss_config, &input.ciphertext, &f_0.0).is_ok()
+            });
+            println!("{}", h_f_0_ok && f_0_ok);
+            Ok(())
+        }
+        Commands::Seal { payload } => {
+            let input = read_obj::<SealInput<E>>()?;
+            let envelope = seal(&input.ciphertext, &input.h_f_0.0, &fs::read(payload)?)?;
+            println!("{}", serde_json::to_string(&envelope)?);
+            Ok(())
+        }
+        Commands::Open => {
+            let input = read_obj::<OpenInput<E>>()?;
+            let payload = open::<E>(&input.h_f_0.0, &input.envelope)?;
+            io::stdout().write_all(&payload)?;
+            Ok(())
+        }
+        Commands::KemEncrypt { payload } => {
+            let input = read_obj::<KemEncryptInput<E>>()?;
+            let ciphertext =
+                kem_encrypt(&pairing_config, &input.commitment.0, &fs::read(payload)?)?;
+            println!("{}", serde_json::to_string(&ciphertext)?);
+            Ok(())
+        }
+        Commands::KemDecrypt => {
+            let input = read_obj::<KemDecryptInput<E>>()?;
+            let payload = kem_decrypt(&input.h_f_0.0, &input.ciphertext)?;
+            io::stdout().write_all(&payload)?;
+            Ok(())
+        }
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    // TODO: fix error handling
+    let args = Cli::parse();
+
+    match args.curve {
+        Curve::Bn254 => run::<Bn254>(args.command),
+        #[cfg(feature = "bls12_381")]
+        Curve::Bls12_381 => run::<ark_bls12_381::Bls12_381>(args.command),
     }
 }
@@ -1,32 +1,39 @@
-use crate::{errors::*, structs::*};
-use ark_bn254::{Bn254, Fr, G1Projective, G2Affine};
+use crate::{
+    engine::PVSSEngine,
+    errors::*,
+    structs::*,
+    transcript::{
+        partial_decryption_challenge, possession_challenge, verify_distribution_challenge,
+        verify_lsss_challenge,
+    },
+};
 use ark_ec::{msm::VariableBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
-use ark_ff::{Field, One, PrimeField, UniformRand, Zero};
-use rand::thread_rng;
-use std::iter::once;
+use ark_ff::{Field, One, PrimeField, Zero};
+use rand::{thread_rng, CryptoRng, Rng, RngCore};
+use std::iter::{self, once};
 use std::ops::Neg;
 
-pub fn gen_all_lagrange_coefficients(n: usize, alpha: Fr) -> Vec<Fr> {
-    let numerator = (1..=n as i64)
-        .map(|x| alpha - Fr::from(x))
-        .reduce(|acc, item| acc * item)
-        .unwrap();
-    let pos = once(Fr::one())
-        .chain(
-            (1..n as i64)
-                .map(|x| Fr::from(x))
-                .scan(Fr::from(1), |state, x| {
-                    *state = *state * x;
-                    Some(*state)
-                }),
-        )
+/// Lagrange coefficients at `alpha` for the points 1..=n
+pub fn gen_all_lagrange_coefficients<F: PrimeField>(n: usize, alpha: F) -> Vec<F> {
+    // NOTE: at one of the points the basis is the unit vector, and alpha - i has no inverse
+    if let Some(i) = (1..=n as u64).position(|x| alpha == F::from(x)) {
+        let mut coefficients = vec![F::zero(); n];
+        coefficients[i] = F::one();
+        return coefficients;
+    }
+    let numerator = (1..=n as u64).map(|x| alpha - F::from(x)).product::<F>();
+    let pos = once(F::one())
+        .chain((1..n as u64).map(F::from).scan(F::one(), |state, x| {
+            *state *= x;
+            Some(*state)
+        }))
         .collect::<Vec<_>>();
-    let neg = once(Fr::one())
+    let neg = once(F::one())
         .chain(
-            (1..n as i64)
-                .map(|x| Fr::from(x).neg())
-                .scan(Fr::from(1), |state, x| {
-                    *state = *state * x;
+            (1..n as u64)
+                .map(|x| F::from(x).neg())
+                .scan(F::one(), |state, x| {
+                    *state *= x;
                     Some(*state)
                 }),
         )
@@ -34,16 +41,16 @@ pub fn gen_all_lagrange_coefficients(n: usize, alpha: Fr) -> Vec<Fr> {
     (1..=n)
         .map(|i| {
             numerator
-                * (alpha - Fr::from(i as i64)).inverse().unwrap()
+                * (alpha - F::from(i as u64)).inverse().unwrap()
                 * (pos[i - 1] * neg[n - i]).inverse().unwrap()
         })
         .collect::<Vec<_>>()
 }
 
-pub fn gen_lagrange_coefficients(x: Vec<Fr>, alpha: Fr) -> Vec<Fr> {
+pub fn gen_lagrange_coefficients<F: PrimeField>(x: Vec<F>, alpha: F) -> Vec<F> {
     x.iter()
         .map(|x_i| {
-            let mut coeff = Fr::one();
+            let mut coeff = F::one();
             for x_j in x.iter() {
                 if x_i == x_j {
                     continue;
@@ -55,139 +62,937 @@ pub fn gen_lagrange_coefficients(x: Vec<Fr>, alpha: Fr) -> Vec<Fr> {
         .collect::<Vec<_>>()
 }
 
-pub fn verify_ciphertext(
-    pvss_config: &PVSSConfig,
-    ciphertext: &PVSSCiphertext,
+pub fn verify_possession<E: PVSSEngine>(
+    pairing_config: &PairingConfig<E>,
+    pk: &E::G2Affine,
+    pop: &ProofOfPossession<E>,
+) -> bool {
+    // NOTE: sk = 0 has a valid proof but makes every share undecryptable
+    if pk.is_zero() {
+        return false;
+    }
+    let r = pairing_config.h.mul(pop.z.into_repr()) - pk.mul(pop.c.into_repr());
+    possession_challenge::<E>(pk, &r.into_affine()) == pop.c
+}
+
+pub fn verify_committee_pops<E: PVSSEngine>(
+    pairing_config: &PairingConfig<E>,
+    committee_pks: &[E::G2Affine],
+    pops: &[ProofOfPossession<E>],
 ) -> Result<(), PVSSError> {
-    // Verify evaluations are correct probabilistically.
-    let mut rng = thread_rng();
-    let alpha = Fr::rand(&mut rng);
-    let lagrange_coefficients =
-        gen_all_lagrange_coefficients(pvss_config.committee_pks.len(), alpha);
+    if pops.len() != committee_pks.len() {
+        return Err(PVSSError::PopCountMismatch {
+            pops: pops.len(),
+            members: committee_pks.len(),
+        });
+    }
+    for (i, (pk, pop)) in committee_pks.iter().zip(pops.iter()).enumerate() {
+        if !verify_possession(pairing_config, pk, pop) {
+            return Err(PVSSError::InvalidProofOfPossession(i));
+        }
+    }
+    Ok(())
+}
 
+// Same length checks as PVSSLib.verifyDistribution
+pub fn check_ciphertext_shape<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+) -> Result<(), PVSSError> {
+    pvss_config.validate()?;
+    let n = pvss_config.num_slots();
+    for (field, expected, actual) in [
+        ("f_i", pvss_config.t, ciphertext.f_i.len()),
+        ("a_i", n, ciphertext.a_i.len()),
+        ("y_i", n, ciphertext.y_i.len()),
+    ] {
+        if actual != expected {
+            return Err(PVSSError::InvalidCiphertextShape {
+                field,
+                expected,
+                actual,
+            });
+        }
+    }
+    Ok(())
+}
+
+pub fn verify_ciphertext<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+) -> Result<(), PVSSError> {
+    let slot_pks = pvss_config
+        .slot_pks()
+        .into_iter()
+        .map(Into::into)
+        .collect::<Vec<_>>();
+    verify_ciphertext_with_pks(pvss_config, ciphertext, &slot_pks)
+}
+
+/// Like `verify_ciphertext`, reusing the prepared slot keys of `prepared`
+pub fn verify_ciphertext_prepared<E: PVSSEngine>(
+    prepared: &PreparedCommittee<E>,
+    ciphertext: &PVSSCiphertext<E>,
+) -> Result<(), PVSSError> {
+    verify_ciphertext_with_pks(&prepared.pvss_config, ciphertext, &prepared.slot_pks)
+}
+
+fn verify_ciphertext_with_pks<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+    slot_pks: &[E::G2Prepared],
+) -> Result<(), PVSSError> {
+    check_ciphertext_shape(pvss_config, ciphertext)?;
+
+    // Verify evaluations are correct probabilistically.
+    // NOTE: alpha is bound to the whole transcript so the dealer cannot grind it
+    let alpha = verify_distribution_challenge(pvss_config, ciphertext);
     {
-        let mut bases = vec![];
-        bases.extend_from_slice(&ciphertext.a_i);
-        let mut scalars = lagrange_coefficients
-            .iter()
-            .map(|l| l.into_repr())
-            .collect::<Vec<_>>();
-        let powers_of_alpha = {
-            let mut current_alpha = Fr::one().neg();
-            let mut powers = vec![];
-            for _ in 0..pvss_config.t {
-                powers.push(current_alpha.into_repr());
-                current_alpha *= &alpha;
-            }
-            powers
-        };
-        bases.extend_from_slice(&ciphertext.f_i);
-        scalars.extend_from_slice(&powers_of_alpha.as_slice());
+        let (bases, scalars) = evaluations_check_terms(pvss_config, ciphertext, alpha);
+        let scalars = scalars.iter().map(|s| s.into_repr()).collect::<Vec<_>>();
         let product = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
         if !product.is_zero() {
-            return Err(PVSSError::EvaluationsCheckError(product.into()));
+            return Err(PVSSError::EvaluationsCheckError(product.to_string()));
         }
     }
 
+    verify_encryptions(
+        &pvss_config.pairing_config,
+        &ciphertext.a_i,
+        &ciphertext.y_i,
+        slot_pks,
+        alpha,
+    )
+}
+
+// Bases and scalars of sum_i L_i(alpha) a_i - sum_j alpha^j f_j, which is zero
+// iff the a_i interpolate the polynomial committed to by f_i
+fn evaluations_check_terms<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+    alpha: E::Fr,
+) -> (Vec<E::G1Affine>, Vec<E::Fr>) {
+    let lagrange_coefficients = gen_all_lagrange_coefficients(pvss_config.num_slots(), alpha);
     let powers_of_alpha = {
-        let mut current_alpha = Fr::one();
+        let mut current_alpha = E::Fr::one().neg();
         let mut powers = vec![];
-        for _ in 0..pvss_config.committee_pks.len() {
-            powers.push(current_alpha.into_repr());
+        for _ in 0..pvss_config.t {
+            powers.push(current_alpha);
             current_alpha *= &alpha;
         }
         powers
     };
+    let bases = [ciphertext.a_i.as_slice(), ciphertext.f_i.as_slice()].concat();
+    let scalars = [lagrange_coefficients, powers_of_alpha].concat();
+    (bases, scalars)
+}
 
-    // NOTE: need -g because we check e(g, ...) = e(..., ...) -> e(..., ...) / e(g, ...) = 1 -> e(..., ...) * e(-g, ...) = 1
-    let (batched_a_i, batched_g_neg) = {
-        let g_neg = pvss_config.pairing_config.g.neg();
-        let batched_a_i = ciphertext
-            .a_i
-            .iter()
-            .zip(powers_of_alpha.iter())
-            .map(|(a, power)| a.mul(*power))
-            .collect::<Vec<_>>();
-        let batched_g_neg = powers_of_alpha
-            .iter()
-            .map(|power| g_neg.mul(*power))
-            .collect::<Vec<_>>();
-        let mut batched_all = vec![];
-        batched_all.extend_from_slice(&batched_a_i);
-        batched_all.extend_from_slice(&batched_g_neg);
-        let batched_all = G1Projective::batch_normalization_into_affine(&batched_all);
-        let batched_a_i = batched_all[..batched_a_i.len()]
-            .into_iter()
-            .map(|x| x.clone())
-            .collect::<Vec<_>>();
-        let batched_g_neg = batched_all[batched_a_i.len()..]
-            .into_iter()
-            .map(|x| x.clone())
-            .collect::<Vec<_>>();
-        (batched_a_i, batched_g_neg)
+pub fn verify_ciphertexts_batch<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertexts: &[PVSSCiphertext<E>],
+) -> Result<(), PVSSError> {
+    verify_ciphertexts_batch_with_rng(pvss_config, ciphertexts, &mut thread_rng())
+}
+
+/// Same as calling verify_ciphertext on every ciphertext, except with probability
+/// about 2^-128: the evaluation checks are folded into one MSM and the encryption
+/// checks into one product of n + 1 pairings. Does not say which ciphertext failed.
+pub fn verify_ciphertexts_batch_with_rng<E: PVSSEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertexts: &[PVSSCiphertext<E>],
+    rng: &mut R,
+) -> Result<(), PVSSError> {
+    for ciphertext in ciphertexts {
+        check_ciphertext_shape(pvss_config, ciphertext)?;
+    }
+
+    let mut bases = vec![];
+    let mut scalars = vec![];
+    for ciphertext in ciphertexts {
+        let alpha = verify_distribution_challenge(pvss_config, ciphertext);
+        let r = E::Fr::from(rng.gen::<u128>());
+        let (ciphertext_bases, ciphertext_scalars) =
+            evaluations_check_terms(pvss_config, ciphertext, alpha);
+        bases.extend(ciphertext_bases);
+        scalars.extend(ciphertext_scalars.iter().map(|s| (*s * r).into_repr()));
+    }
+    let product = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
+    if !product.is_zero() {
+        return Err(PVSSError::EvaluationsCheckError(product.to_string()));
+    }
+
+    // Checks prod_i e(sum_b r_bi a_bi, pk_i) = e(g, sum_bi r_bi y_bi)
+    let coefficients = ciphertexts
+        .iter()
+        .map(|_| {
+            (0..pvss_config.num_slots())
+                .map(|_| E::Fr::from(rng.gen::<u128>()).into_repr())
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    let batched_a_i = pvss_config
+        .slot_pks()
+        .into_iter()
+        .enumerate()
+        .map(|(i, pk)| {
+            let (a_i, r_i): (Vec<_>, Vec<_>) = ciphertexts
+                .iter()
+                .zip(coefficients.iter())
+                .map(|(ciphertext, r)| (ciphertext.a_i[i], r[i]))
+                .unzip();
+            let a = VariableBaseMSM::multi_scalar_mul(&a_i, &r_i).into_affine();
+            (a.into(), pk.into())
+        })
+        .collect::<Vec<_>>();
+    let (y_i, r_i): (Vec<_>, Vec<_>) = ciphertexts
+        .iter()
+        .zip(coefficients.iter())
+        .flat_map(|(ciphertext, r)| ciphertext.y_i.iter().copied().zip(r.iter().copied()))
+        .unzip();
+    let batched_y = VariableBaseMSM::multi_scalar_mul(&y_i, &r_i).into_affine();
+
+    let pairs = batched_a_i
+        .into_iter()
+        .chain(once((
+            pvss_config.pairing_config.g.neg().into(),
+            batched_y.into(),
+        )))
+        .collect::<Vec<_>>();
+    if !E::product_of_pairings(pairs.iter()).is_one() {
+        return Err(PVSSError::RatioIncorrect);
+    }
+    Ok(())
+}
+
+// Checks e(g, y_i) = e(a_i, pk_i) for every i, batched with powers of alpha as
+// prod_i e(alpha^i a_i, pk_i) * e(-g, sum_i alpha^i y_i) = 1: n + 1 Miller loops
+pub(crate) fn verify_encryptions<E: PairingEngine>(
+    pairing_config: &PairingConfig<E>,
+    a_i: &[E::G1Affine],
+    y_i: &[E::G2Affine],
+    pks: &[E::G2Prepared],
+    alpha: E::Fr,
+) -> Result<(), PVSSError> {
+    let powers_of_alpha = {
+        let mut current_alpha = E::Fr::one();
+        let mut powers = vec![];
+        for _ in 0..a_i.len() {
+            powers.push(current_alpha.into_repr());
+            current_alpha *= &alpha;
+        }
+        powers
     };
 
+    let batched_a_i = a_i
+        .iter()
+        .zip(powers_of_alpha.iter())
+        .map(|(a, power)| a.mul(*power))
+        .collect::<Vec<_>>();
+    let batched_a_i = E::G1Projective::batch_normalization_into_affine(&batched_a_i);
+    // NOTE: every e(g, y_i) shares g, so that side folds into one G2 MSM
+    let batched_y_i = VariableBaseMSM::multi_scalar_mul(y_i, &powers_of_alpha).into_affine();
+
     // Verify evaluations are encrypted correctly.
     let pairs = batched_a_i
         .into_iter()
-        .zip(ciphertext.y_i.iter())
-        .zip(batched_g_neg.into_iter())
         .enumerate()
-        .map(|(i, ((a, y), g_neg))| {
-            let pk = pvss_config
-                .committee_pks
-                .get(i)
-                .ok_or(PVSSError::InvalidParticipantId(i))?;
-            let pairs = vec![(g_neg.into(), (*y).into()), (a.into(), (*pk).into())];
-
-            Ok(pairs)
+        .map(|(i, a)| {
+            let pk = pks.get(i).ok_or(PVSSError::InvalidParticipantId(i))?;
+            Ok((a.into(), pk.clone()))
         })
-        .collect::<Result<Vec<_>, _>>()?
-        .into_iter()
-        .flatten()
-        .collect::<Vec<_>>();
-    if !Bn254::product_of_pairings(pairs.iter()).is_one() {
+        .chain(once(Ok((
+            pairing_config.g.neg().into(),
+            batched_y_i.into(),
+        ))))
+        .collect::<Result<Vec<_>, PVSSError>>()?;
+    if !E::product_of_pairings(pairs.iter()).is_one() {
         return Err(PVSSError::RatioIncorrect);
     }
     Ok(())
 }
 
-pub fn verify_share(
-    pvss_config: &PVSSConfig,
-    pvss_ciphertext: &PVSSCiphertext,
-    decrypted_share: G2Affine,
+pub fn verify_ciphertext_lsss<E: PVSSEngine>(
+    lsss_config: &LsssConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+) -> Result<(), PVSSError> {
+    lsss_config.validate()?;
+    let (num_rows, num_cols) = (lsss_config.lsss.num_rows(), lsss_config.lsss.num_cols());
+    for (field, expected, actual) in [
+        ("f_i", num_cols, ciphertext.f_i.len()),
+        ("a_i", num_rows, ciphertext.a_i.len()),
+        ("y_i", num_rows, ciphertext.y_i.len()),
+    ] {
+        if actual != expected {
+            return Err(PVSSError::InvalidCiphertextShape {
+                field,
+                expected,
+                actual,
+            });
+        }
+    }
+
+    // Checks sum_i alpha^i (a_i - sum_j M_ij f_j) = 0, i.e. a_i = g^{<M_i, rho>}
+    let alpha = verify_lsss_challenge(lsss_config, ciphertext);
+    let powers_of_alpha = iter::successors(Some(E::Fr::one()), |x| Some(*x * alpha))
+        .take(num_rows)
+        .collect::<Vec<_>>();
+    let column_scalars = (0..num_cols).map(|j| {
+        -lsss_config
+            .lsss
+            .matrix
+            .iter()
+            .zip(powers_of_alpha.iter())
+            .map(|(row, power)| row[j] * power)
+            .sum::<E::Fr>()
+    });
+    let scalars = powers_of_alpha
+        .iter()
+        .copied()
+        .chain(column_scalars)
+        .map(|x| x.into_repr())
+        .collect::<Vec<_>>();
+    let bases = [ciphertext.a_i.as_slice(), ciphertext.f_i.as_slice()].concat();
+    let product = VariableBaseMSM::multi_scalar_mul(&bases, &scalars);
+    if !product.is_zero() {
+        return Err(PVSSError::EvaluationsCheckError(product.to_string()));
+    }
+
+    verify_encryptions(
+        &lsss_config.pairing_config,
+        &ciphertext.a_i,
+        &ciphertext.y_i,
+        &lsss_config
+            .row_pks()
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<_>>(),
+        alpha,
+    )
+}
+
+/// Combines the decrypted shares of any authorized set of rows
+pub fn reconstruct_lsss<E: PairingEngine>(
+    lsss_config: &LsssConfig<E>,
+    pvss_ciphertext: Option<&PVSSCiphertext<E>>,
+    shares: &[(usize, E::G2Affine)],
+) -> Result<E::G2Affine, PVSSError> {
+    lsss_config.validate()?;
+    let num_rows = lsss_config.lsss.num_rows();
+    for (position, (row, _)) in shares.iter().enumerate() {
+        if *row >= num_rows {
+            return Err(PVSSError::InvalidParticipantId(*row));
+        }
+        if shares[..position].iter().any(|(r, _)| r == row) {
+            return Err(PVSSError::DuplicateIndex(*row));
+        }
+    }
+    let (rows, decrypted_shares): (Vec<_>, Vec<_>) = shares.iter().copied().unzip();
+    let coefficients = lsss_config
+        .lsss
+        .reconstruction_coefficients(&rows)
+        .ok_or(PVSSError::Unauthorized)?
+        .iter()
+        .map(|c| c.into_repr())
+        .collect::<Vec<_>>();
+    let secret = VariableBaseMSM::multi_scalar_mul(&decrypted_shares, &coefficients).into_affine();
+
+    if let Some(pvss_ciphertext) = pvss_ciphertext {
+        let pairing_config = &lsss_config.pairing_config;
+        let h = pairing_config.h.into();
+        check_commitment(
+            pairing_config,
+            &h,
+            first_commitment(pvss_ciphertext)?,
+            &secret,
+        )?;
+    }
+    Ok(secret)
+}
+
+pub fn verify_refresh<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    refresh: &PVSSCiphertext<E>,
+) -> Result<(), PVSSError> {
+    if !refresh.f_i.first().is_some_and(|f| f.is_zero()) {
+        return Err(PVSSError::RefreshSecretNotZero);
+    }
+    verify_ciphertext(pvss_config, refresh)
+}
+
+// Coefficient-wise sum, valid since every part of a dealing is linear in its polynomial
+pub(crate) fn sum_ciphertexts<'a, E: PairingEngine>(
+    first: &PVSSCiphertext<E>,
+    rest: impl IntoIterator<Item = &'a PVSSCiphertext<E>>,
+) -> Result<PVSSCiphertext<E>, PVSSError> {
+    fn add<G: AffineCurve>(acc: &[G], x: &[G]) -> Result<Vec<G>, PVSSError> {
+        if acc.len() != x.len() {
+            return Err(PVSSError::CiphertextShapeMismatch);
+        }
+        Ok(acc.iter().zip(x.iter()).map(|(a, b)| *a + *b).collect())
+    }
+
+    rest.into_iter().try_fold(
+        PVSSCiphertext {
+            f_i: first.f_i.clone(),
+            a_i: first.a_i.clone(),
+            y_i: first.y_i.clone(),
+        },
+        |acc, x| {
+            Ok(PVSSCiphertext {
+                f_i: add(&acc.f_i, &x.f_i)?,
+                a_i: add(&acc.a_i, &x.a_i)?,
+                y_i: add(&acc.y_i, &x.y_i)?,
+            })
+        },
+    )
+}
+
+// Adds the refreshes onto the dealing, keeping f_i[0] and so h^{f_0}
+pub fn refresh_ciphertext<E: PairingEngine>(
+    ciphertext: &PVSSCiphertext<E>,
+    refreshes: &[PVSSCiphertext<E>],
+) -> Result<PVSSCiphertext<E>, PVSSError> {
+    sum_ciphertexts(ciphertext, refreshes)
+}
+
+/// Checks a whole refresh round: every refresh is a valid zero-secret dealing
+/// and `refreshed` is `ciphertext` plus all of them
+pub fn verify_refreshed_ciphertext<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+    refreshes: &[PVSSCiphertext<E>],
+    refreshed: &PVSSCiphertext<E>,
+) -> Result<(), PVSSError> {
+    for refresh in refreshes.iter() {
+        verify_refresh(pvss_config, refresh)?;
+    }
+    let expected = refresh_ciphertext(ciphertext, refreshes)?;
+    if expected.f_i != refreshed.f_i
+        || expected.a_i != refreshed.a_i
+        || expected.y_i != refreshed.y_i
+    {
+        return Err(PVSSError::RefreshMismatch);
+    }
+    Ok(())
+}
+
+pub fn verify_share<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    decrypted_share: E::G2Affine,
     i: usize,
 ) -> Result<(), PVSSError> {
-    let g_neg = pvss_config.pairing_config.g.neg();
-    let pairs = vec![
+    let h = pvss_config.pairing_config.h.into();
+    check_share(
+        &pvss_config.pairing_config,
+        &h,
+        pvss_ciphertext,
+        decrypted_share,
+        i,
+    )
+}
+
+pub fn verify_share_prepared<E: PairingEngine>(
+    prepared: &PreparedCommittee<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    decrypted_share: E::G2Affine,
+    i: usize,
+) -> Result<(), PVSSError> {
+    let pairing_config = &prepared.pvss_config.pairing_config;
+    check_share(
+        pairing_config,
+        &prepared.h,
+        pvss_ciphertext,
+        decrypted_share,
+        i,
+    )
+}
+
+// Checks e(g, S_i) = e(a_i, h)
+fn check_share<E: PairingEngine>(
+    pairing_config: &PairingConfig<E>,
+    h: &E::G2Prepared,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    decrypted_share: E::G2Affine,
+    i: usize,
+) -> Result<(), PVSSError> {
+    let a_i = pvss_ciphertext
+        .a_i
+        .get(i)
+        .ok_or(PVSSError::InvalidParticipantId(i))?;
+    let g_neg = pairing_config.g.neg();
+    let pairs = [
         (g_neg.into(), decrypted_share.into()),
-        (
-            pvss_ciphertext.a_i[i].into(),
-            pvss_config.pairing_config.h.into(),
-        ),
+        ((*a_i).into(), h.clone()),
     ];
-    if !Bn254::product_of_pairings(pairs.iter()).is_one() {
+    if !E::product_of_pairings(pairs.iter()).is_one() {
         return Err(PVSSError::RatioIncorrect);
     }
     Ok(())
 }
 
 // Assumes everything has already been verified
-pub fn combine_shares(
-    decrypted_shares: &Vec<G2Affine>,
-    indices: &Vec<usize>,
-) -> Result<G2Affine, PVSSError> {
+pub fn combine_shares<G: AffineCurve>(
+    decrypted_shares: &[G],
+    indices: &[usize],
+) -> Result<G, PVSSError> {
+    combine_shares_at(decrypted_shares, indices, G::ScalarField::zero())
+}
+
+/// Interpolates the shared polynomial "in the exponent" at `alpha` instead of 0
+pub fn combine_shares_at<G: AffineCurve>(
+    decrypted_shares: &[G],
+    indices: &[usize],
+    alpha: G::ScalarField,
+) -> Result<G, PVSSError> {
+    if decrypted_shares.len() != indices.len() {
+        return Err(PVSSError::ShareCountMismatch {
+            shares: decrypted_shares.len(),
+            indices: indices.len(),
+        });
+    }
+    check_distinct_indices(indices)?;
     // Recombine secrets
     let x = indices
         .iter()
-        .map(|i| Fr::from((*i + 1) as i64))
+        .map(|i| G::ScalarField::from((*i + 1) as u64))
         .collect::<Vec<_>>();
-    let lagrange_coefficients = gen_lagrange_coefficients(x, Fr::zero())
+    let lagrange_coefficients = gen_lagrange_coefficients(x, alpha)
         .iter()
         .map(|l| l.into_repr())
         .collect::<Vec<_>>();
 
     let product =
-        VariableBaseMSM::multi_scalar_mul(&decrypted_shares, &lagrange_coefficients).into_affine();
+        VariableBaseMSM::multi_scalar_mul(decrypted_shares, &lagrange_coefficients).into_affine();
     Ok(product)
 }
+
+// Checks e(f_i[0], h) = e(g, h_f_0) for a dealing to `pvss_config`
+pub fn verify_secret<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    h_f_0: &E::G2Affine,
+) -> Result<(), PVSSError> {
+    check_ciphertext_shape(pvss_config, pvss_ciphertext)?;
+    let pairing_config = &pvss_config.pairing_config;
+    let h = pairing_config.h.into();
+    check_commitment(
+        pairing_config,
+        &h,
+        first_commitment(pvss_ciphertext)?,
+        h_f_0,
+    )
+}
+
+fn check_commitment<E: PairingEngine>(
+    pairing_config: &PairingConfig<E>,
+    h: &E::G2Prepared,
+    commitment: &E::G1Affine,
+    h_secret: &E::G2Affine,
+) -> Result<(), PVSSError> {
+    let pairs = [
+        ((*commitment).into(), h.clone()),
+        (pairing_config.g.neg().into(), (*h_secret).into()),
+    ];
+    if !E::product_of_pairings(pairs.iter()).is_one() {
+        return Err(PVSSError::SecretMismatch);
+    }
+    Ok(())
+}
+
+// For when f_0 itself has been revealed: checks g^{f_0} = f_i[0]
+pub fn verify_secret_scalar<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    f_0: &E::Fr,
+) -> Result<(), PVSSError> {
+    check_ciphertext_shape(pvss_config, pvss_ciphertext)?;
+    if pvss_config
+        .pairing_config
+        .g
+        .mul(f_0.into_repr())
+        .into_affine()
+        != *first_commitment(pvss_ciphertext)?
+    {
+        return Err(PVSSError::SecretMismatch);
+    }
+    Ok(())
+}
+
+fn first_commitment<E: PairingEngine>(
+    pvss_ciphertext: &PVSSCiphertext<E>,
+) -> Result<&E::G1Affine, PVSSError> {
+    pvss_ciphertext
+        .f_i
+        .first()
+        .ok_or(PVSSError::InvalidCiphertextShape {
+            field: "f_i",
+            expected: 1,
+            actual: 0,
+        })
+}
+
+/// Combines shares for `pvss_config`, refusing anything that cannot yield the
+/// dealt secret. With `pvss_ciphertext`, also checks e(f_i[0], h) = e(g, secret).
+pub fn reconstruct_secret<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: Option<&PVSSCiphertext<E>>,
+    shares: &[(usize, E::G2Affine)],
+) -> Result<E::G2Affine, PVSSError> {
+    pvss_config.validate()?;
+    let (indices, decrypted_shares) = check_share_set(pvss_config, shares)?;
+    let secret = combine_shares(&decrypted_shares, &indices)?;
+
+    if let Some(pvss_ciphertext) = pvss_ciphertext {
+        verify_secret(pvss_config, pvss_ciphertext, &secret)?;
+    }
+    Ok(secret)
+}
+
+pub fn reconstruct_secret_prepared<E: PairingEngine>(
+    prepared: &PreparedCommittee<E>,
+    pvss_ciphertext: Option<&PVSSCiphertext<E>>,
+    shares: &[(usize, E::G2Affine)],
+) -> Result<E::G2Affine, PVSSError> {
+    let pvss_config = &prepared.pvss_config;
+    pvss_config.validate()?;
+    let (indices, decrypted_shares) = check_share_set(pvss_config, shares)?;
+    let secret = combine_shares(&decrypted_shares, &indices)?;
+
+    if let Some(pvss_ciphertext) = pvss_ciphertext {
+        let commitment = first_commitment(pvss_ciphertext)?;
+        check_commitment(
+            &pvss_config.pairing_config,
+            &prepared.h,
+            commitment,
+            &secret,
+        )?;
+    }
+    Ok(secret)
+}
+
+// Splits shares into (indices, shares) once they are in range and enough for t
+fn check_share_set<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    shares: &[(usize, E::G2Affine)],
+) -> Result<(Vec<usize>, Vec<E::G2Affine>), PVSSError> {
+    let n = pvss_config.num_slots();
+    if let Some((i, _)) = shares.iter().find(|(i, _)| *i >= n) {
+        return Err(PVSSError::InvalidParticipantId(*i));
+    }
+    if shares.len() < pvss_config.t {
+        return Err(PVSSError::NotEnoughShares {
+            have: shares.len(),
+            need: pvss_config.t,
+        });
+    }
+    let (indices, decrypted_shares): (Vec<_>, Vec<_>) = shares.iter().copied().unzip();
+    Ok((indices, decrypted_shares))
+}
+
+/// g^{s_j} for every packed secret: p(-j) evaluated on the coefficient commitments
+pub fn packed_commitments<E: PairingEngine>(
+    packed_config: &PackedPVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+) -> Result<Vec<E::G1Affine>, PVSSError> {
+    packed_config.validate()?;
+    check_ciphertext_shape(&packed_config.pvss_config, pvss_ciphertext)?;
+    Ok((0..packed_config.k)
+        .map(|j| {
+            let x = -E::Fr::from(j as u64);
+            let powers = iter::successors(Some(E::Fr::one()), |x_pow| Some(*x_pow * x))
+                .take(pvss_ciphertext.f_i.len())
+                .map(|x_pow| x_pow.into_repr())
+                .collect::<Vec<_>>();
+            VariableBaseMSM::multi_scalar_mul(&pvss_ciphertext.f_i, &powers).into_affine()
+        })
+        .collect())
+}
+
+/// Recovers h^{s_j} for packed secret `j` from t + k shares. With
+/// `pvss_ciphertext`, also checks it against the packed commitment g^{s_j}.
+pub fn reconstruct_packed<E: PairingEngine>(
+    packed_config: &PackedPVSSConfig<E>,
+    pvss_ciphertext: Option<&PVSSCiphertext<E>>,
+    shares: &[(usize, E::G2Affine)],
+    j: usize,
+) -> Result<E::G2Affine, PVSSError> {
+    packed_config.validate()?;
+    if j >= packed_config.k {
+        return Err(PVSSError::InvalidSecretIndex {
+            j,
+            k: packed_config.k,
+        });
+    }
+    let pvss_config = &packed_config.pvss_config;
+    let (indices, decrypted_shares) = check_share_set(pvss_config, shares)?;
+    let secret = combine_shares_at(&decrypted_shares, &indices, -E::Fr::from(j as u64))?;
+
+    if let Some(pvss_ciphertext) = pvss_ciphertext {
+        let commitments = packed_commitments(packed_config, pvss_ciphertext)?;
+        let pairing_config = &pvss_config.pairing_config;
+        let h = pairing_config.h.into();
+        check_commitment(pairing_config, &h, &commitments[j], &secret)?;
+    }
+    Ok(secret)
+}
+
+pub fn verify_partial_decryption<E: PVSSEngine>(
+    pairing_config: &PairingConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    kem_ciphertext: &KemCiphertext<E>,
+    partial_decryption: &PartialDecryption<E>,
+) -> Result<(), PVSSError> {
+    if pvss_ciphertext.f_i.first() != Some(&kem_ciphertext.commitment) {
+        return Err(PVSSError::CommitmentMismatch);
+    }
+    let PartialDecryption { i, d_i, c, z } = partial_decryption;
+    let a_i = pvss_ciphertext
+        .a_i
+        .get(*i)
+        .ok_or(PVSSError::InvalidParticipantId(*i))?;
+    let u = kem_ciphertext.u;
+
+    // t_1 = e(g, z) / e(a_i, h)^c, t_2 = e(u, z) / d_i^c
+    let pairs = [
+        (pairing_config.g.into(), (*z).into()),
+        (
+            a_i.mul((-*c).into_repr()).into_affine().into(),
+            pairing_config.h.into(),
+        ),
+    ];
+    let t_1 = E::product_of_pairings(pairs.iter());
+    let t_2 = E::pairing(u, *z) * d_i.pow((-*c).into_repr());
+    if partial_decryption_challenge::<E>(*i, a_i, &u, d_i, &t_1, &t_2) != *c {
+        return Err(PVSSError::InvalidPartialDecryption(*i));
+    }
+    Ok(())
+}
+
+// NOTE: gen_lagrange_coefficients silently gives wrong coefficients for repeated x
+fn check_distinct_indices(indices: &[usize]) -> Result<(), PVSSError> {
+    for (position, i) in indices.iter().enumerate() {
+        if indices[..position].contains(i) {
+            return Err(PVSSError::DuplicateIndex(*i));
+        }
+    }
+    Ok(())
+}
+
+// Interpolates e(u, h)^{f_0} in GT, assumes the partial decryptions have been verified
+pub fn combine_partial_decryptions<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    partial_decryptions: &[PartialDecryption<E>],
+) -> Result<E::Fqk, PVSSError> {
+    pvss_config.validate()?;
+    let n = pvss_config.num_slots();
+    if let Some(pd) = partial_decryptions.iter().find(|pd| pd.i >= n) {
+        return Err(PVSSError::InvalidParticipantId(pd.i));
+    }
+    let indices = partial_decryptions
+        .iter()
+        .map(|pd| pd.i)
+        .collect::<Vec<_>>();
+    check_distinct_indices(&indices)?;
+    if indices.len() < pvss_config.t {
+        return Err(PVSSError::NotEnoughShares {
+            have: indices.len(),
+            need: pvss_config.t,
+        });
+    }
+    let x = partial_decryptions
+        .iter()
+        .map(|pd| E::Fr::from((pd.i + 1) as u64))
+        .collect::<Vec<_>>();
+    let lagrange_coefficients = gen_lagrange_coefficients(x, E::Fr::zero());
+    Ok(partial_decryptions
+        .iter()
+        .zip(lagrange_coefficients.iter())
+        .map(|(pd, l)| pd.d_i.pow(l.into_repr()))
+        .product())
+}
+
+pub fn verify_shares_batch<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    decrypted_shares: &[(usize, E::G2Affine)],
+) -> Result<(), PVSSError> {
+    verify_shares_batch_with_rng(
+        pvss_config,
+        pvss_ciphertext,
+        decrypted_shares,
+        &mut thread_rng(),
+    )
+}
+
+// Same as calling verify_share on every share, but with a single product of 2 pairings
+pub fn verify_shares_batch_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    decrypted_shares: &[(usize, E::G2Affine)],
+    rng: &mut R,
+) -> Result<(), PVSSError> {
+    if let Some((i, _)) = decrypted_shares
+        .iter()
+        .find(|(i, _)| *i >= pvss_ciphertext.a_i.len())
+    {
+        return Err(PVSSError::InvalidParticipantId(*i));
+    }
+    if !batch_check_shares(pvss_config, pvss_ciphertext, decrypted_shares, rng) {
+        return Err(PVSSError::RatioIncorrect);
+    }
+    Ok(())
+}
+
+// Checks e(g, sum r_j S_j) = e(sum r_j a_j, h) for random 128-bit r_j
+fn batch_check_shares<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    shares: &[(usize, E::G2Affine)],
+    rng: &mut R,
+) -> bool {
+    let coefficients = shares
+        .iter()
+        .map(|_| E::Fr::from(rng.gen::<u128>()).into_repr())
+        .collect::<Vec<_>>();
+    let (a_i, decrypted_shares): (Vec<_>, Vec<_>) = shares
+        .iter()
+        .map(|(i, share)| (pvss_ciphertext.a_i[*i], *share))
+        .unzip();
+    let batched_a_i = VariableBaseMSM::multi_scalar_mul(&a_i, &coefficients).into_affine();
+    let batched_shares =
+        VariableBaseMSM::multi_scalar_mul(&decrypted_shares, &coefficients).into_affine();
+
+    let g_neg = pvss_config.pairing_config.g.neg();
+    let pairs = [
+        (g_neg.into(), batched_shares.into()),
+        (batched_a_i.into(), pvss_config.pairing_config.h.into()),
+    ];
+    E::product_of_pairings(pairs.iter()).is_one()
+}
+
+// Bisects until every share failing the batched check is isolated.
+// Returns positions into `shares`, offset by `offset`.
+fn find_invalid_shares<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    shares: &[(usize, E::G2Affine)],
+    offset: usize,
+    rng: &mut R,
+) -> Vec<usize> {
+    if shares.is_empty() || batch_check_shares(pvss_config, pvss_ciphertext, shares, rng) {
+        return vec![];
+    }
+    if shares.len() == 1 {
+        return vec![offset];
+    }
+    let mid = shares.len() / 2;
+    let mut invalid =
+        find_invalid_shares(pvss_config, pvss_ciphertext, &shares[..mid], offset, rng);
+    invalid.extend(find_invalid_shares(
+        pvss_config,
+        pvss_ciphertext,
+        &shares[mid..],
+        offset + mid,
+        rng,
+    ));
+    invalid
+}
+
+pub fn reconstruct<E: PairingEngine>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    shares: &[(usize, E::G2Affine)],
+) -> Result<Reconstruction<E>, PVSSError> {
+    reconstruct_with_rng(pvss_config, pvss_ciphertext, shares, &mut thread_rng())
+}
+
+// Verifies every share before combining, dropping the ones that do not match a_i
+pub fn reconstruct_with_rng<E: PairingEngine, R: RngCore + CryptoRng>(
+    pvss_config: &PVSSConfig<E>,
+    pvss_ciphertext: &PVSSCiphertext<E>,
+    shares: &[(usize, E::G2Affine)],
+    rng: &mut R,
+) -> Result<Reconstruction<E>, PVSSError> {
+    pvss_config.validate()?;
+    check_ciphertext_shape(pvss_config, pvss_ciphertext)?;
+    let (in_range, out_of_range): (Vec<_>, Vec<_>) = shares
+        .iter()
+        .partition(|(i, _)| *i < pvss_ciphertext.a_i.len());
+    let invalid = find_invalid_shares(pvss_config, pvss_ciphertext, &in_range, 0, rng);
+
+    // NOTE: valid shares for the same index are equal, so keeping the first one is enough
+    let mut indices = vec![];
+    let mut decrypted_shares = vec![];
+    for (position, (i, share)) in in_range.iter().enumerate() {
+        if !invalid.contains(&position) && !indices.contains(i) {
+            indices.push(*i);
+            decrypted_shares.push(*share);
+        }
+    }
+    let rejected = out_of_range
+        .iter()
+        .map(|(i, _)| *i)
+        .chain(invalid.iter().map(|position| in_range[*position].0))
+        .collect::<Vec<_>>();
+
+    if indices.len() < pvss_config.t {
+        return Err(PVSSError::NotEnoughShares {
+            have: indices.len(),
+            need: pvss_config.t,
+        });
+    }
+    let secret = combine_shares(&decrypted_shares, &indices)?;
+    Ok(Reconstruction { secret, rejected })
+}
+
+// The encryption check of verify_ciphertext, and the 2n-pairing version it
+// replaced, for tests and the verify bench to compare
+#[cfg(any(test, feature = "bench"))]
+pub mod encryption_checks {
+    use super::*;
+
+    /// `verify_encryptions` with the keys prepared per call, as in verify_ciphertext
+    pub fn verify_encryptions_n_plus_1<E: PairingEngine>(
+        pairing_config: &PairingConfig<E>,
+        a_i: &[E::G1Affine],
+        y_i: &[E::G2Affine],
+        pks: &[E::G2Affine],
+        alpha: E::Fr,
+    ) -> Result<(), PVSSError> {
+        let pks = pks.iter().map(|pk| (*pk).into()).collect::<Vec<_>>();
+        verify_encryptions(pairing_config, a_i, y_i, &pks, alpha)
+    }
+
+    /// One `(-g alpha^i, y_i)` and one `(a_i alpha^i, pk_i)` pair per slot
+    pub fn verify_encryptions_2n<E: PairingEngine>(
+        pairing_config: &PairingConfig<E>,
+        a_i: &[E::G1Affine],
+        y_i: &[E::G2Affine],
+        pks: &[E::G2Affine],
+        alpha: E::Fr,
+    ) -> Result<(), PVSSError> {
+        let g_neg = pairing_config.g.neg();
+        let pairs = a_i
+            .iter()
+            .zip(y_i.iter())
+            .zip(pks.iter())
+            .zip(iter::successors(Some(E::Fr::one()), |power| {
+                Some(*power * alpha)
+            }))
+            .flat_map(|(((a, y), pk), power)| {
+                [
+                    (
+                        g_neg.mul(power.into_repr()).into_affine().into(),
+                        (*y).into(),
+                    ),
+                    (a.mul(power.into_repr()).into_affine().into(), (*pk).into()),
+                ]
+            })
+            .collect::<Vec<_>>();
+        if !E::product_of_pairings(pairs.iter()).is_one() {
+            return Err(PVSSError::RatioIncorrect);
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,94 @@
+use crate::{engine::PVSSEngine, errors::*, structs::*, transcript::ciphertext_digest};
+
+use ark_ff::{BigInteger, PrimeField};
+use chacha20poly1305::{
+    aead::{Aead, KeyInit, Payload},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use hkdf::Hkdf;
+use rand::{thread_rng, CryptoRng, RngCore};
+use sha2::Sha256;
+
+pub const SEAL_KDF_INFO: &[u8] = b"cassiopeia-pvss-v1/seal";
+
+// HKDF-SHA256 over the big-endian coordinates of h^{f_0}, salted with the dealing's hash
+fn derive_key<E: PVSSEngine>(secret: &E::G2Affine, ciphertext_hash: &[u8; 32]) -> Key {
+    let (x, y) = E::g2_coordinates(secret);
+    let ikm = [x, y]
+        .iter()
+        .flat_map(E::fqe_to_components)
+        .flat_map(|c| c.into_repr().to_bytes_be())
+        .collect::<Vec<_>>();
+    let mut key = Key::default();
+    Hkdf::<Sha256>::new(Some(ciphertext_hash), &ikm)
+        .expand(SEAL_KDF_INFO, &mut key)
+        .expect("32 bytes is a valid HKDF-SHA256 output length");
+    key
+}
+
+/// Encrypts `payload` under the secret `h_f_0` of `ciphertext`
+pub fn seal<E: PVSSEngine>(
+    ciphertext: &PVSSCiphertext<E>,
+    h_f_0: &E::G2Affine,
+    payload: &[u8],
+) -> Result<SealedEnvelope, PVSSError> {
+    seal_with_rng(ciphertext, h_f_0, payload, &mut thread_rng())
+}
+
+pub fn seal_with_rng<E: PVSSEngine, R: RngCore + CryptoRng>(
+    ciphertext: &PVSSCiphertext<E>,
+    h_f_0: &E::G2Affine,
+    payload: &[u8],
+    rng: &mut R,
+) -> Result<SealedEnvelope, PVSSError> {
+    let ciphertext_hash = ciphertext_digest(ciphertext);
+    let key = derive_key::<E>(h_f_0, &ciphertext_hash);
+    let (nonce, payload) = encrypt_payload(&key, &ciphertext_hash, payload, rng)?;
+    Ok(SealedEnvelope {
+        ciphertext_hash,
+        nonce,
+        payload,
+    })
+}
+
+/// Decrypts an envelope with the secret recovered by `combine_shares`.
+/// Callers that hold the dealing should also check
+/// `envelope.ciphertext_hash == ciphertext_digest(ciphertext)`.
+pub fn open<E: PVSSEngine>(
+    h_f_0: &E::G2Affine,
+    envelope: &SealedEnvelope,
+) -> Result<Vec<u8>, PVSSError> {
+    let key = derive_key::<E>(h_f_0, &envelope.ciphertext_hash);
+    decrypt_payload(
+        &key,
+        &envelope.ciphertext_hash,
+        &envelope.nonce,
+        &envelope.payload,
+    )
+}
+
+// ChaCha20-Poly1305 with a random nonce, shared with the KEM
+pub(crate) fn encrypt_payload<R: RngCore + CryptoRng>(
+    key: &Key,
+    aad: &[u8],
+    payload: &[u8],
+    rng: &mut R,
+) -> Result<([u8; 12], Vec<u8>), PVSSError> {
+    let mut nonce = [0u8; 12];
+    rng.fill_bytes(&mut nonce);
+    let payload = ChaCha20Poly1305::new(key)
+        .encrypt(Nonce::from_slice(&nonce), Payload { msg: payload, aad })
+        .map_err(|_| PVSSError::SealError)?;
+    Ok((nonce, payload))
+}
+
+pub(crate) fn decrypt_payload(
+    key: &Key,
+    aad: &[u8],
+    nonce: &[u8; 12],
+    payload: &[u8],
+) -> Result<Vec<u8>, PVSSError> {
+    ChaCha20Poly1305::new(key)
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: payload, aad })
+        .map_err(|_| PVSSError::OpenError)
+}
@@ -1,146 +1,124 @@
-use ark_bn254::{Fq, Fq2, Fr, G1Affine, G2Affine};
+use ark_bn254::Bn254;
+use ark_ec::PairingEngine;
 use ark_ff::PrimeField;
 use serde::de::{self, Deserializer, MapAccess, SeqAccess, Visitor};
 use serde::ser::{SerializeStruct, SerializeTuple};
 use serde::{Deserialize, Serialize};
 use std::fmt;
+use std::marker::PhantomData;
 
-use crate::structs::{PVSSCiphertext, PVSSSecrets};
+use crate::engine::PVSSEngine;
+use crate::errors::PVSSError;
+use crate::structs::{
+    KemCiphertext, PVSSCiphertext, PVSSSecrets, ProofOfPossession, SealedEnvelope,
+};
 
 #[derive(Copy, Clone)]
-pub struct FrSerializable(Fr);
+pub struct FrSerializable<E: PairingEngine = Bn254>(pub E::Fr);
 #[derive(Copy, Clone)]
-pub struct FqSerializable(Fq);
+pub struct FqSerializable<E: PairingEngine = Bn254>(pub E::Fq);
 #[derive(Copy, Clone)]
-pub struct Fq2Serializable(Fq2);
+pub struct Fq2Serializable<E: PairingEngine = Bn254>(pub E::Fqe);
 #[derive(Copy, Clone)]
-pub struct G1AffineSerializable(G1Affine);
+pub struct G1AffineSerializable<E: PairingEngine = Bn254>(pub E::G1Affine);
 #[derive(Copy, Clone)]
-pub struct G2AffineSerializable(G2Affine);
+pub struct G2AffineSerializable<E: PairingEngine = Bn254>(pub E::G2Affine);
 
-impl From<Fr> for FrSerializable {
-    fn from(item: Fr) -> Self {
-        FrSerializable(item)
-    }
-}
-
-impl From<Fq> for FqSerializable {
-    fn from(item: Fq) -> Self {
-        FqSerializable(item)
-    }
-}
-
-impl From<Fq2> for Fq2Serializable {
-    fn from(item: Fq2) -> Self {
-        Fq2Serializable(item)
-    }
-}
-
-impl From<G1Affine> for G1AffineSerializable {
-    fn from(item: G1Affine) -> Self {
-        G1AffineSerializable(item)
-    }
-}
-
-impl From<G2Affine> for G2AffineSerializable {
-    fn from(item: G2Affine) -> Self {
-        G2AffineSerializable(item)
-    }
-}
+// NOTE: skip the on-curve and subgroup checks, only use these for trusted input
+#[derive(Copy, Clone)]
+pub struct UncheckedG1AffineSerializable<E: PairingEngine = Bn254>(pub E::G1Affine);
+#[derive(Copy, Clone)]
+pub struct UncheckedG2AffineSerializable<E: PairingEngine = Bn254>(pub E::G2Affine);
 
-impl Into<Fr> for FrSerializable {
-    fn into(self) -> Fr {
-        self.0
-    }
+fn field_to_hex<F: PrimeField>(x: &F) -> String {
+    "0x".to_string() + &x.into_repr().to_string().to_lowercase()
 }
 
-impl Into<Fq> for FqSerializable {
-    fn into(self) -> Fq {
-        self.0
+fn field_from_hex<F: PrimeField, Err: de::Error>(value: &str) -> Result<F, Err> {
+    let invalid = || Err::custom(PVSSError::InvalidHex(value.to_string()));
+    let hex_value = value.strip_prefix("0x").ok_or_else(invalid)?;
+    let bytes = hex::decode(hex_value).map_err(|_| invalid())?;
+    if bytes.is_empty() {
+        return Err(invalid());
     }
-}
-
-impl Into<Fq2> for Fq2Serializable {
-    fn into(self) -> Fq2 {
-        self.0
+    let x = F::from_be_bytes_mod_order(bytes.as_slice());
+    if x.into_repr().to_string().to_lowercase() != hex_value {
+        return Err(Err::custom(PVSSError::FieldElementTooLarge(
+            value.to_string(),
+        )));
     }
+    Ok(x)
 }
 
-impl Into<G1Affine> for G1AffineSerializable {
-    fn into(self) -> G1Affine {
-        self.0
-    }
+fn bytes_to_hex(bytes: &[u8]) -> String {
+    "0x".to_string() + &hex::encode(bytes)
 }
 
-impl Into<G2Affine> for G2AffineSerializable {
-    fn into(self) -> G2Affine {
-        self.0
-    }
+fn bytes_from_hex<Err: de::Error>(value: &str) -> Result<Vec<u8>, Err> {
+    value
+        .strip_prefix("0x")
+        .and_then(|value| hex::decode(value).ok())
+        .ok_or_else(|| Err::custom(PVSSError::InvalidHex(value.to_string())))
 }
 
-impl Serialize for FrSerializable {
+impl<E: PairingEngine> Serialize for FrSerializable<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let s = "0x".to_string() + &self.0.into_repr().to_string().to_lowercase();
-        serializer.serialize_newtype_struct("FrSerializable", &s)
+        serializer.serialize_newtype_struct("FrSerializable", &field_to_hex(&self.0))
     }
 }
 
-impl Serialize for FqSerializable {
+impl<E: PairingEngine> Serialize for FqSerializable<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
-        let s = "0x".to_string() + &self.0.into_repr().to_string().to_lowercase();
-        serializer.serialize_newtype_struct("FqSerializable", &s)
+        serializer.serialize_newtype_struct("FqSerializable", &field_to_hex(&self.0))
     }
 }
 
-impl Serialize for Fq2Serializable {
+impl<E: PVSSEngine> Serialize for Fq2Serializable<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         let mut state = serializer.serialize_tuple(2)?;
-        let c0 = "0x".to_string() + &self.0.c0.into_repr().to_string().to_lowercase();
-        let c1 = "0x".to_string() + &self.0.c1.into_repr().to_string().to_lowercase();
-        state.serialize_element(&c1)?; // NOTE: Important! EIP-197 pairing expects reverse order!
-        state.serialize_element(&c0)?;
+        let [c0, c1] = E::fqe_to_components(&self.0);
+        state.serialize_element(&field_to_hex(&c1))?; // NOTE: Important! EIP-197 pairing expects reverse order!
+        state.serialize_element(&field_to_hex(&c0))?;
         state.end()
     }
 }
 
-impl Serialize for G1AffineSerializable {
+impl<E: PVSSEngine> Serialize for G1AffineSerializable<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         let mut state = serializer.serialize_struct("G1AffineSerializable", 2)?;
-        let x: FqSerializable = self.0.x.into();
-        let y: FqSerializable = self.0.y.into();
-        state.serialize_field("x", &x)?;
-        state.serialize_field("y", &y)?;
+        let (x, y) = E::g1_coordinates(&self.0);
+        state.serialize_field("x", &FqSerializable::<E>(x))?;
+        state.serialize_field("y", &FqSerializable::<E>(y))?;
         state.end()
     }
 }
 
-impl Serialize for G2AffineSerializable {
+impl<E: PVSSEngine> Serialize for G2AffineSerializable<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         let mut state = serializer.serialize_struct("G2AffineSerializable", 2)?;
-        let x: Fq2Serializable = self.0.x.into();
-        let y: Fq2Serializable = self.0.y.into();
-        state.serialize_field("x", &x)?;
-        state.serialize_field("y", &y)?;
+        let (x, y) = E::g2_coordinates(&self.0);
+        state.serialize_field("x", &Fq2Serializable::<E>(x))?;
+        state.serialize_field("y", &Fq2Serializable::<E>(y))?;
         state.end()
     }
 }
 
-impl Serialize for PVSSCiphertext {
+impl<E: PVSSEngine> Serialize for PVSSCiphertext<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -151,162 +129,124 @@ impl Serialize for PVSSCiphertext {
             &self
                 .f_i
                 .iter()
-                .map(|&f| f.into())
-                .collect::<Vec<G1AffineSerializable>>(),
+                .map(|&f| G1AffineSerializable::<E>(f))
+                .collect::<Vec<_>>(),
         )?;
         state.serialize_field(
             "a_i",
             &self
                 .a_i
                 .iter()
-                .map(|&a| a.into())
-                .collect::<Vec<G1AffineSerializable>>(),
+                .map(|&a| G1AffineSerializable::<E>(a))
+                .collect::<Vec<_>>(),
         )?;
         state.serialize_field(
             "y_i",
             &self
                 .y_i
                 .iter()
-                .map(|&y| y.into())
-                .collect::<Vec<G2AffineSerializable>>(),
+                .map(|&y| G2AffineSerializable::<E>(y))
+                .collect::<Vec<_>>(),
         )?;
         state.end()
     }
 }
 
-impl Serialize for PVSSSecrets {
+impl<E: PVSSEngine> Serialize for PVSSSecrets<E> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
     {
         let mut state = serializer.serialize_struct("PVSSSecrets", 2)?;
-        let f_0_serializable: FrSerializable = self.f_0.into();
-        let h_f_0_serializable: G2AffineSerializable = self.h_f_0.into();
-        state.serialize_field("f_0", &f_0_serializable)?;
-        state.serialize_field("h_f_0", &h_f_0_serializable)?;
+        state.serialize_field("f_0", &FrSerializable::<E>(self.f_0))?;
+        state.serialize_field("h_f_0", &G2AffineSerializable::<E>(self.h_f_0))?;
         state.end()
     }
 }
 
-impl<'de> Deserialize<'de> for FrSerializable {
-    fn deserialize<D>(deserializer: D) -> Result<FrSerializable, D::Error>
+impl<'de, E: PairingEngine> Deserialize<'de> for FrSerializable<E> {
+    fn deserialize<D>(deserializer: D) -> Result<FrSerializable<E>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct FrVisitor;
+        struct FrVisitor<E>(PhantomData<E>);
 
-        impl<'de> Visitor<'de> for FrVisitor {
-            type Value = FrSerializable;
+        impl<'de, E: PairingEngine> Visitor<'de> for FrVisitor<E> {
+            type Value = FrSerializable<E>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("Big number corresponding to Fr")
             }
 
-            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            fn visit_str<Err>(self, value: &str) -> Result<Self::Value, Err>
             where
-                E: de::Error,
+                Err: de::Error,
             {
-                if value.len() <= 2 || &value[..2] != "0x" {
-                    return Err(E::custom("Invalid hex string"));
-                }
-                let value = &value[2..];
-                if let Some(bytes) = hex::decode(value).ok() {
-                    let fr = Fr::from_be_bytes_mod_order(bytes.as_slice());
-                    if fr.into_repr().to_string().to_lowercase() != value {
-                        return Err(E::custom(format!(
-                            "Hex value too large {} {} {:?}",
-                            value,
-                            bytes.len(),
-                            bytes
-                        )));
-                    }
-                    Ok(FrSerializable(fr))
-                } else {
-                    return Err(E::custom("Invalid hex string"));
-                }
+                Ok(FrSerializable(field_from_hex(value)?))
             }
         }
 
-        deserializer.deserialize_str(FrVisitor)
+        deserializer.deserialize_str(FrVisitor(PhantomData))
     }
 }
 
-impl<'de> Deserialize<'de> for FqSerializable {
-    fn deserialize<D>(deserializer: D) -> Result<FqSerializable, D::Error>
+impl<'de, E: PairingEngine> Deserialize<'de> for FqSerializable<E> {
+    fn deserialize<D>(deserializer: D) -> Result<FqSerializable<E>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct FqVisitor;
+        struct FqVisitor<E>(PhantomData<E>);
 
-        impl<'de> Visitor<'de> for FqVisitor {
-            type Value = FqSerializable;
+        impl<'de, E: PairingEngine> Visitor<'de> for FqVisitor<E> {
+            type Value = FqSerializable<E>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("Big number corresponding to Fr")
+                formatter.write_str("Big number corresponding to Fq")
             }
 
-            fn visit_str<E>(self, value: &str) -> Result<Self::Value, E>
+            fn visit_str<Err>(self, value: &str) -> Result<Self::Value, Err>
             where
-                E: de::Error,
+                Err: de::Error,
             {
-                if value.len() <= 2 || &value[..2] != "0x" {
-                    return Err(E::custom("Invalid hex string"));
-                }
-                let value = &value[2..];
-                if let Some(bytes) = hex::decode(value).ok() {
-                    let fq = Fq::from_be_bytes_mod_order(bytes.as_slice());
-                    if fq.into_repr().to_string().to_lowercase() != value {
-                        return Err(E::custom(format!(
-                            "Hex value too large {} {} {:?}",
-                            value,
-                            bytes.len(),
-                            bytes
-                        )));
-                    }
-                    Ok(FqSerializable(fq))
-                } else {
-                    return Err(E::custom("Invalid hex string"));
-                }
+                Ok(FqSerializable(field_from_hex(value)?))
             }
         }
 
-        deserializer.deserialize_str(FqVisitor)
+        deserializer.deserialize_str(FqVisitor(PhantomData))
     }
 }
 
-impl<'de> Deserialize<'de> for Fq2Serializable {
+impl<'de, E: PVSSEngine> Deserialize<'de> for Fq2Serializable<E> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct Fq2Visitor;
+        struct Fq2Visitor<E>(PhantomData<E>);
 
-        impl<'de> Visitor<'de> for Fq2Visitor {
-            type Value = Fq2Serializable;
+        impl<'de, E: PVSSEngine> Visitor<'de> for Fq2Visitor<E> {
+            type Value = Fq2Serializable<E>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("struct Fq2Serializable")
             }
 
-            fn visit_seq<V>(self, mut seq: V) -> Result<Fq2Serializable, V::Error>
+            fn visit_seq<V>(self, mut seq: V) -> Result<Fq2Serializable<E>, V::Error>
             where
                 V: SeqAccess<'de>,
             {
                 // NOTE: Important! EIP-197 pairing expects reverse order!
-                let c1: FqSerializable = seq
-                    .next_element::<FqSerializable>()?
-                    .ok_or_else(|| de::Error::invalid_length(0, &self))?
-                    .into();
-                let c0: FqSerializable = seq
-                    .next_element::<FqSerializable>()?
-                    .ok_or_else(|| de::Error::invalid_length(1, &self))?
-                    .into();
-                Ok(Fq2Serializable(Fq2::new(c0.into(), c1.into())))
+                let c1: FqSerializable<E> = seq
+                    .next_element::<FqSerializable<E>>()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let c0: FqSerializable<E> = seq
+                    .next_element::<FqSerializable<E>>()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                Ok(Fq2Serializable(E::fqe_from_components([c0.0, c1.0])))
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["c0", "c1"];
-        deserializer.deserialize_struct("Fq2Serializable", FIELDS, Fq2Visitor)
+        const FIELDS: &[&str] = &["c0", "c1"];
+        deserializer.deserialize_struct("Fq2Serializable", FIELDS, Fq2Visitor(PhantomData))
     }
 }
 
@@ -317,105 +257,153 @@ enum ECPointField {
     Y,
 }
 
-impl<'de> Deserialize<'de> for G1AffineSerializable {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+struct G1AffineVisitor<E> {
+    checked: bool,
+    _engine: PhantomData<E>,
+}
+
+impl<'de, E: PVSSEngine> Visitor<'de> for G1AffineVisitor<E> {
+    type Value = E::G1Affine;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct G1AffineSerializable")
+    }
+
+    fn visit_map<V>(self, mut map: V) -> Result<E::G1Affine, V::Error>
     where
-        D: Deserializer<'de>,
+        V: MapAccess<'de>,
     {
-        struct G1AffineVisitor;
+        let mut x: Option<FqSerializable<E>> = None;
+        let mut y: Option<FqSerializable<E>> = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                ECPointField::X => {
+                    if x.is_some() {
+                        return Err(de::Error::duplicate_field("x"));
+                    }
+                    x = Some(map.next_value()?);
+                }
+                ECPointField::Y => {
+                    if y.is_some() {
+                        return Err(de::Error::duplicate_field("y"));
+                    }
+                    y = Some(map.next_value()?);
+                }
+            }
+        }
+        let x = x.ok_or_else(|| de::Error::missing_field("x"))?.0;
+        let y = y.ok_or_else(|| de::Error::missing_field("y"))?.0;
+        if !self.checked {
+            return Ok(E::g1_from_coordinates_unchecked(x, y));
+        }
+        E::g1_from_coordinates(x, y)
+            .map_err(|e| de::Error::custom(format!("Invalid G1 point: {}", e)))
+    }
+}
 
-        impl<'de> Visitor<'de> for G1AffineVisitor {
-            type Value = G1AffineSerializable;
+struct G2AffineVisitor<E> {
+    checked: bool,
+    _engine: PhantomData<E>,
+}
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("struct G1AffineSerializable")
-            }
+impl<'de, E: PVSSEngine> Visitor<'de> for G2AffineVisitor<E> {
+    type Value = E::G2Affine;
 
-            fn visit_map<V>(self, mut map: V) -> Result<G1AffineSerializable, V::Error>
-            where
-                V: MapAccess<'de>,
-            {
-                let mut x: Option<FqSerializable> = None;
-                let mut y: Option<FqSerializable> = None;
-                while let Some(key) = map.next_key()? {
-                    match key {
-                        ECPointField::X => {
-                            if x.is_some() {
-                                return Err(de::Error::duplicate_field("x"));
-                            }
-                            x = Some(map.next_value()?);
-                        }
-                        ECPointField::Y => {
-                            if y.is_some() {
-                                return Err(de::Error::duplicate_field("y"));
-                            }
-                            y = Some(map.next_value()?);
-                        }
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("struct G2AffineSerializable")
+    }
+
+    fn visit_map<V>(self, mut map: V) -> Result<E::G2Affine, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let mut x: Option<Fq2Serializable<E>> = None;
+        let mut y: Option<Fq2Serializable<E>> = None;
+        while let Some(key) = map.next_key()? {
+            match key {
+                ECPointField::X => {
+                    if x.is_some() {
+                        return Err(de::Error::duplicate_field("x"));
+                    }
+                    x = Some(map.next_value()?);
+                }
+                ECPointField::Y => {
+                    if y.is_some() {
+                        return Err(de::Error::duplicate_field("y"));
                     }
+                    y = Some(map.next_value()?);
                 }
-                let x: Fq = x.ok_or_else(|| de::Error::missing_field("x"))?.into();
-                let y: Fq = y.ok_or_else(|| de::Error::missing_field("y"))?.into();
-                Ok(G1AffineSerializable(G1Affine::new(
-                    x.into(),
-                    y.into(),
-                    false,
-                )))
             }
         }
-
-        const FIELDS: &'static [&'static str] = &["x", "y"];
-        deserializer.deserialize_struct("G1AffineSerializable", FIELDS, G1AffineVisitor)
+        let x = x.ok_or_else(|| de::Error::missing_field("x"))?.0;
+        let y = y.ok_or_else(|| de::Error::missing_field("y"))?.0;
+        if !self.checked {
+            return Ok(E::g2_from_coordinates_unchecked(x, y));
+        }
+        E::g2_from_coordinates(x, y)
+            .map_err(|e| de::Error::custom(format!("Invalid G2 point: {}", e)))
     }
 }
 
-impl<'de> Deserialize<'de> for G2AffineSerializable {
+const EC_POINT_FIELDS: &[&str] = &["x", "y"];
+
+impl<'de, E: PVSSEngine> Deserialize<'de> for G1AffineSerializable<E> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct G2AffineVisitor;
-
-        impl<'de> Visitor<'de> for G2AffineVisitor {
-            type Value = G2AffineSerializable;
+        let visitor = G1AffineVisitor::<E> {
+            checked: true,
+            _engine: PhantomData,
+        };
+        deserializer
+            .deserialize_struct("G1AffineSerializable", EC_POINT_FIELDS, visitor)
+            .map(G1AffineSerializable)
+    }
+}
 
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("struct G2AffineSerializable")
-            }
+impl<'de, E: PVSSEngine> Deserialize<'de> for UncheckedG1AffineSerializable<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = G1AffineVisitor::<E> {
+            checked: false,
+            _engine: PhantomData,
+        };
+        deserializer
+            .deserialize_struct("G1AffineSerializable", EC_POINT_FIELDS, visitor)
+            .map(UncheckedG1AffineSerializable)
+    }
+}
 
-            fn visit_map<V>(self, mut map: V) -> Result<G2AffineSerializable, V::Error>
-            where
-                V: MapAccess<'de>,
-            {
-                let mut x: Option<Fq2Serializable> = None;
-                let mut y: Option<Fq2Serializable> = None;
-                while let Some(key) = map.next_key()? {
-                    match key {
-                        ECPointField::X => {
-                            if x.is_some() {
-                                return Err(de::Error::duplicate_field("x"));
-                            }
-                            x = Some(map.next_value()?);
-                        }
-                        ECPointField::Y => {
-                            if y.is_some() {
-                                return Err(de::Error::duplicate_field("y"));
-                            }
-                            y = Some(map.next_value()?);
-                        }
-                    }
-                }
-                let x: Fq2 = x.ok_or_else(|| de::Error::missing_field("x"))?.into();
-                let y: Fq2 = y.ok_or_else(|| de::Error::missing_field("y"))?.into();
-                Ok(G2AffineSerializable(G2Affine::new(
-                    x.into(),
-                    y.into(),
-                    false,
-                )))
-            }
-        }
+impl<'de, E: PVSSEngine> Deserialize<'de> for G2AffineSerializable<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = G2AffineVisitor::<E> {
+            checked: true,
+            _engine: PhantomData,
+        };
+        deserializer
+            .deserialize_struct("G2AffineSerializable", EC_POINT_FIELDS, visitor)
+            .map(G2AffineSerializable)
+    }
+}
 
-        const FIELDS: &'static [&'static str] = &["x", "y"];
-        deserializer.deserialize_struct("G2AffineSerializable", FIELDS, G2AffineVisitor)
+impl<'de, E: PVSSEngine> Deserialize<'de> for UncheckedG2AffineSerializable<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = G2AffineVisitor::<E> {
+            checked: false,
+            _engine: PhantomData,
+        };
+        deserializer
+            .deserialize_struct("G2AffineSerializable", EC_POINT_FIELDS, visitor)
+            .map(UncheckedG2AffineSerializable)
     }
 }
 
@@ -428,27 +416,27 @@ enum PVSSCiphertextField {
     Y_i,
 }
 
-impl<'de> Deserialize<'de> for PVSSCiphertext {
+impl<'de, E: PVSSEngine> Deserialize<'de> for PVSSCiphertext<E> {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        struct PVSSCiphertextVisitor;
+        struct PVSSCiphertextVisitor<E>(PhantomData<E>);
 
-        impl<'de> Visitor<'de> for PVSSCiphertextVisitor {
-            type Value = PVSSCiphertext;
+        impl<'de, E: PVSSEngine> Visitor<'de> for PVSSCiphertextVisitor<E> {
+            type Value = PVSSCiphertext<E>;
 
             fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
                 formatter.write_str("struct PVSSCiphertext")
             }
 
-            fn visit_map<V>(self, mut map: V) -> Result<PVSSCiphertext, V::Error>
+            fn visit_map<V>(self, mut map: V) -> Result<PVSSCiphertext<E>, V::Error>
             where
                 V: MapAccess<'de>,
             {
-                let mut f_i: Option<Vec<G1AffineSerializable>> = None;
-                let mut a_i: Option<Vec<G1AffineSerializable>> = None;
-                let mut y_i: Option<Vec<G2AffineSerializable>> = None;
+                let mut f_i: Option<Vec<G1AffineSerializable<E>>> = None;
+                let mut a_i: Option<Vec<G1AffineSerializable<E>>> = None;
+                let mut y_i: Option<Vec<G2AffineSerializable<E>>> = None;
                 while let Some(key) = map.next_key()? {
                     match key {
                         PVSSCiphertextField::F_i => {
@@ -474,23 +462,140 @@ impl<'de> Deserialize<'de> for PVSSCiphertext {
                 let f_i = f_i
                     .ok_or_else(|| de::Error::missing_field("f_i"))?
                     .into_iter()
-                    .map(|f| f.into())
-                    .collect::<Vec<G1Affine>>();
+                    .map(|f| f.0)
+                    .collect::<Vec<_>>();
                 let a_i = a_i
                     .ok_or_else(|| de::Error::missing_field("a_i"))?
                     .into_iter()
-                    .map(|a| a.into())
-                    .collect::<Vec<G1Affine>>();
+                    .map(|a| a.0)
+                    .collect::<Vec<_>>();
                 let y_i = y_i
                     .ok_or_else(|| de::Error::missing_field("y_i"))?
                     .into_iter()
-                    .map(|y| y.into())
-                    .collect::<Vec<G2Affine>>();
+                    .map(|y| y.0)
+                    .collect::<Vec<_>>();
                 Ok(PVSSCiphertext { f_i, a_i, y_i })
             }
         }
 
-        const FIELDS: &'static [&'static str] = &["f_i", "a_i", "y_i"];
-        deserializer.deserialize_struct("PVSSCiphertext", FIELDS, PVSSCiphertextVisitor)
+        const FIELDS: &[&str] = &["f_i", "a_i", "y_i"];
+        deserializer.deserialize_struct(
+            "PVSSCiphertext",
+            FIELDS,
+            PVSSCiphertextVisitor(PhantomData),
+        )
+    }
+}
+
+impl Serialize for SealedEnvelope {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("SealedEnvelope", 3)?;
+        state.serialize_field("ciphertext_hash", &bytes_to_hex(&self.ciphertext_hash))?;
+        state.serialize_field("nonce", &bytes_to_hex(&self.nonce))?;
+        state.serialize_field("payload", &bytes_to_hex(&self.payload))?;
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for SealedEnvelope {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        struct SealedEnvelopeHex {
+            ciphertext_hash: String,
+            nonce: String,
+            payload: String,
+        }
+
+        let raw = SealedEnvelopeHex::deserialize(deserializer)?;
+        let ciphertext_hash = bytes_from_hex::<D::Error>(&raw.ciphertext_hash)?
+            .try_into()
+            .map_err(|_| de::Error::custom("ciphertext_hash must be 32 bytes"))?;
+        let nonce = bytes_from_hex::<D::Error>(&raw.nonce)?
+            .try_into()
+            .map_err(|_| de::Error::custom("nonce must be 12 bytes"))?;
+        Ok(SealedEnvelope {
+            ciphertext_hash,
+            nonce,
+            payload: bytes_from_hex(&raw.payload)?,
+        })
+    }
+}
+
+impl<E: PVSSEngine> Serialize for KemCiphertext<E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("KemCiphertext", 4)?;
+        state.serialize_field("commitment", &G1AffineSerializable::<E>(self.commitment))?;
+        state.serialize_field("u", &G1AffineSerializable::<E>(self.u))?;
+        state.serialize_field("nonce", &bytes_to_hex(&self.nonce))?;
+        state.serialize_field("payload", &bytes_to_hex(&self.payload))?;
+        state.end()
+    }
+}
+
+impl<'de, E: PVSSEngine> Deserialize<'de> for KemCiphertext<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(bound = "E: PVSSEngine")]
+        struct KemCiphertextHex<E: PVSSEngine> {
+            commitment: G1AffineSerializable<E>,
+            u: G1AffineSerializable<E>,
+            nonce: String,
+            payload: String,
+        }
+
+        let raw = KemCiphertextHex::<E>::deserialize(deserializer)?;
+        let nonce = bytes_from_hex::<D::Error>(&raw.nonce)?
+            .try_into()
+            .map_err(|_| de::Error::custom("nonce must be 12 bytes"))?;
+        Ok(KemCiphertext {
+            commitment: raw.commitment.0,
+            u: raw.u.0,
+            nonce,
+            payload: bytes_from_hex(&raw.payload)?,
+        })
+    }
+}
+
+impl<E: PairingEngine> Serialize for ProofOfPossession<E> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut state = serializer.serialize_struct("ProofOfPossession", 2)?;
+        state.serialize_field("c", &FrSerializable::<E>(self.c))?;
+        state.serialize_field("z", &FrSerializable::<E>(self.z))?;
+        state.end()
+    }
+}
+
+impl<'de, E: PairingEngine> Deserialize<'de> for ProofOfPossession<E> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(bound = "E: PairingEngine")]
+        struct ProofOfPossessionFr<E: PairingEngine> {
+            c: FrSerializable<E>,
+            z: FrSerializable<E>,
+        }
+
+        let raw = ProofOfPossessionFr::<E>::deserialize(deserializer)?;
+        Ok(ProofOfPossession {
+            c: raw.c.0,
+            z: raw.z.0,
+        })
     }
 }
@@ -1,43 +1,365 @@
-use ark_bn254::{g1, g2, Fr, G1Affine, G2Affine};
+use ark_bn254::Bn254;
+use ark_ec::{msm::FixedBaseMSM, AffineCurve, PairingEngine, ProjectiveCurve};
+use ark_ff::PrimeField;
+use std::{iter, ops::Range};
 
-pub struct PairingConfig {
-    pub g: G1Affine,
-    pub h: G2Affine,
+use crate::{
+    engine::PVSSEngine,
+    errors::PVSSError,
+    lsss::{Lsss, Policy},
+    public::verify_committee_pops,
+};
+
+pub struct PairingConfig<E: PairingEngine = Bn254> {
+    pub g: E::G1Affine,
+    pub h: E::G2Affine,
 }
 
-pub struct PVSSConfig {
-    pub pairing_config: PairingConfig,
-    pub committee_pks: Vec<G2Affine>,
+pub struct PVSSConfig<E: PairingEngine = Bn254> {
+    pub pairing_config: PairingConfig<E>,
+    pub committee_pks: Vec<E::G2Affine>,
+    /// Number of consecutive evaluation points (slots) held by each member
+    pub weights: Vec<usize>,
+    /// Threshold in slots
     pub t: usize,
 }
 
-pub struct PVSSCiphertext {
-    pub f_i: Vec<G1Affine>,
-    pub a_i: Vec<G1Affine>,
-    pub y_i: Vec<G2Affine>,
+/// Like `PVSSConfig`, but shares follow `lsss` instead of a t-of-n polynomial.
+/// Ciphertexts have one `f_i` per LSSS column and one `a_i`/`y_i` per row.
+pub struct LsssConfig<E: PairingEngine = Bn254> {
+    pub pairing_config: PairingConfig<E>,
+    pub committee_pks: Vec<E::G2Affine>,
+    pub lsss: Lsss<E::Fr>,
+}
+
+/// k secrets packed at x = 0, -1, ..., -(k - 1) of one polynomial of degree t + k - 1.
+/// `pvss_config.t` is t + k: fewer shares than t reveal nothing about any secret.
+pub struct PackedPVSSConfig<E: PairingEngine = Bn254> {
+    pub pvss_config: PVSSConfig<E>,
+    pub k: usize,
+}
+
+/// Pairing preprocessing and a fixed-base table for a committee that deals and
+/// verifies many times. Rebuild it whenever `pvss_config` changes.
+pub struct PreparedCommittee<E: PairingEngine = Bn254> {
+    pub pvss_config: PVSSConfig<E>,
+    /// One per slot, in `slot_pks` order
+    pub slot_pks: Vec<E::G2Prepared>,
+    pub h: E::G2Prepared,
+    pub g_table: FixedBaseTable<E::G1Projective>,
+}
+
+/// Precomputed multiples of a base for `FixedBaseMSM`
+pub struct FixedBaseTable<G: ProjectiveCurve> {
+    window: usize,
+    table: Vec<Vec<G::Affine>>,
+}
+
+pub struct PVSSCiphertext<E: PairingEngine = Bn254> {
+    pub f_i: Vec<E::G1Affine>,
+    pub a_i: Vec<E::G1Affine>,
+    pub y_i: Vec<E::G2Affine>,
+}
+
+pub struct PVSSSecrets<E: PairingEngine = Bn254> {
+    pub f_0: E::Fr,
+    pub h_f_0: E::G2Affine,
+}
+
+/// A ciphertext with the secrets it deals
+pub type Dealing<E = Bn254> = (PVSSCiphertext<E>, PVSSSecrets<E>);
+
+/// `secrets[j]` is the value at x = -j and `h_secrets[j]` is h^{secrets[j]}
+pub struct PackedPVSSSecrets<E: PairingEngine = Bn254> {
+    pub secrets: Vec<E::Fr>,
+    pub h_secrets: Vec<E::G2Affine>,
+}
+
+pub struct Reconstruction<E: PairingEngine = Bn254> {
+    pub secret: E::G2Affine,
+    pub rejected: Vec<usize>,
+}
+
+/// Payload encrypted under the secret of the dealing with hash `ciphertext_hash`
+pub struct SealedEnvelope {
+    pub ciphertext_hash: [u8; 32],
+    pub nonce: [u8; 12],
+    pub payload: Vec<u8>,
+}
+
+/// Payload encrypted to the commitment `f_i[0]` of a dealing, with ephemeral `u = g^r`
+pub struct KemCiphertext<E: PairingEngine = Bn254> {
+    pub commitment: E::G1Affine,
+    pub u: E::G1Affine,
+    pub nonce: [u8; 12],
+    pub payload: Vec<u8>,
 }
 
-pub struct PVSSSecrets {
-    pub f_0: Fr,
-    pub h_f_0: G2Affine,
+/// `d_i = e(u, S_i)` for the KEM ephemeral `u`, with a proof `(c, z)` that `S_i`
+/// is the share committed to by `a_i`
+pub struct PartialDecryption<E: PairingEngine = Bn254> {
+    pub i: usize,
+    pub d_i: E::Fqk,
+    pub c: E::Fr,
+    pub z: E::G2Affine,
 }
 
-impl PairingConfig {
+/// Signature of knowledge of `f_0` for `commitment = g^{f_0}` and of `sk` for
+/// `pk = h^{sk}`, where `pk` is `committee_pks[dealer]`.
+/// `ciphertext_hash` is the `ciphertext_digest` of the contributed dealing.
+#[derive(Clone)]
+pub struct ContributionProof<E: PairingEngine = Bn254> {
+    pub dealer: usize,
+    pub pk: E::G2Affine,
+    pub ciphertext_hash: [u8; 32],
+    pub commitment: E::G1Affine,
+    pub c: E::Fr,
+    pub z: E::Fr,
+    pub z_sk: E::Fr,
+}
+
+/// Sum of several dealers' ciphertexts, for a secret none of them knows
+pub struct AggregatedCiphertext<E: PairingEngine = Bn254> {
+    pub ciphertext: PVSSCiphertext<E>,
+    pub proofs: Vec<ContributionProof<E>>,
+}
+
+/// Schnorr proof of knowledge of `sk` for `pk = h^{sk}`
+#[derive(Clone)]
+pub struct ProofOfPossession<E: PairingEngine = Bn254> {
+    pub c: E::Fr,
+    pub z: E::Fr,
+}
+
+impl<E: PairingEngine> Default for PairingConfig<E> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<E: PairingEngine> PairingConfig<E> {
     pub fn new() -> Self {
         PairingConfig {
-            g: G1Affine::new(g1::G1_GENERATOR_X, g1::G1_GENERATOR_Y, false),
-            h: G2Affine::new(g2::G2_GENERATOR_X, g2::G2_GENERATOR_Y, false),
+            g: E::G1Affine::prime_subgroup_generator(),
+            h: E::G2Affine::prime_subgroup_generator(),
         }
     }
 }
 
-impl PVSSConfig {
-    pub fn new(pairing_config: PairingConfig, committee_pks: Vec<G2Affine>, t: usize) -> Self {
-        assert!(t <= committee_pks.len());
-        PVSSConfig {
+impl<E: PairingEngine> PVSSConfig<E> {
+    pub fn new(
+        pairing_config: PairingConfig<E>,
+        committee_pks: Vec<E::G2Affine>,
+        t: usize,
+    ) -> Result<Self, PVSSError> {
+        let weights = vec![1; committee_pks.len()];
+        Self::new_weighted(pairing_config, committee_pks, weights, t)
+    }
+
+    /// Member j holds the slots after those of members 0..j, so a ciphertext
+    /// for weights [2, 1] has the same layout as one for pks [pk_0, pk_0, pk_1]
+    pub fn new_weighted(
+        pairing_config: PairingConfig<E>,
+        committee_pks: Vec<E::G2Affine>,
+        weights: Vec<usize>,
+        t: usize,
+    ) -> Result<Self, PVSSError> {
+        let pvss_config = PVSSConfig {
             pairing_config,
             committee_pks,
+            weights,
             t,
+        };
+        pvss_config.validate()?;
+        Ok(pvss_config)
+    }
+
+    // NOTE: fields are public, so entry points re-check this
+    pub fn validate(&self) -> Result<(), PVSSError> {
+        if self.weights.len() != self.committee_pks.len() {
+            return Err(PVSSError::WeightCountMismatch {
+                weights: self.weights.len(),
+                members: self.committee_pks.len(),
+            });
+        }
+        if let Some(member) = self.weights.iter().position(|w| *w == 0) {
+            return Err(PVSSError::InvalidWeight(member));
+        }
+        let n = self.num_slots();
+        if self.t == 0 || self.t > n {
+            return Err(PVSSError::InvalidThreshold { t: self.t, n });
+        }
+        Ok(())
+    }
+
+    pub fn num_slots(&self) -> usize {
+        self.weights.iter().sum()
+    }
+
+    /// Slot indices (evaluation points minus one) held by `member`
+    pub fn slots(&self, member: usize) -> Option<Range<usize>> {
+        let weight = *self.weights.get(member)?;
+        let start = self.weights[..member].iter().sum::<usize>();
+        Some(start..start + weight)
+    }
+
+    pub fn member_of_slot(&self, slot: usize) -> Option<usize> {
+        let mut end = 0;
+        self.weights.iter().position(|w| {
+            end += w;
+            slot < end
+        })
+    }
+
+    /// The public key encrypting each slot
+    pub fn slot_pks(&self) -> Vec<E::G2Affine> {
+        self.committee_pks
+            .iter()
+            .zip(self.weights.iter())
+            .flat_map(|(pk, w)| iter::repeat_n(*pk, *w))
+            .collect()
+    }
+}
+
+impl<E: PVSSEngine> PVSSConfig<E> {
+    /// Like `new`, but rejects keys without a valid proof of possession
+    pub fn new_with_pops(
+        pairing_config: PairingConfig<E>,
+        committee_pks: Vec<E::G2Affine>,
+        pops: &[ProofOfPossession<E>],
+        t: usize,
+    ) -> Result<Self, PVSSError> {
+        verify_committee_pops(&pairing_config, &committee_pks, pops)?;
+        Self::new(pairing_config, committee_pks, t)
+    }
+}
+
+impl<E: PairingEngine> PreparedCommittee<E> {
+    pub fn new(pvss_config: PVSSConfig<E>) -> Result<Self, PVSSError> {
+        pvss_config.validate()?;
+        // Sized for one dealing: t coefficients and n evaluations under g
+        let num_scalars = pvss_config.t + pvss_config.num_slots();
+        let pairing_config = &pvss_config.pairing_config;
+        Ok(PreparedCommittee {
+            slot_pks: pvss_config.slot_pks().into_iter().map(Into::into).collect(),
+            h: pairing_config.h.into(),
+            g_table: FixedBaseTable::new(pairing_config.g, num_scalars),
+            pvss_config,
+        })
+    }
+}
+
+impl<G: ProjectiveCurve> FixedBaseTable<G> {
+    /// Picks the window for roughly `num_scalars` multiplications per call
+    pub fn new(base: G::Affine, num_scalars: usize) -> Self {
+        let window = FixedBaseMSM::get_mul_window_size(num_scalars);
+        let table = FixedBaseMSM::get_window_table(
+            G::ScalarField::size_in_bits(),
+            window,
+            base.into_projective(),
+        );
+        FixedBaseTable { window, table }
+    }
+
+    pub fn mul(&self, scalars: &[G::ScalarField]) -> Vec<G::Affine> {
+        G::batch_normalization_into_affine(&FixedBaseMSM::multi_scalar_mul(
+            G::ScalarField::size_in_bits(),
+            self.window,
+            &self.table,
+            scalars,
+        ))
+    }
+}
+
+impl<E: PairingEngine> PackedPVSSConfig<E> {
+    /// Packs `k` secrets with privacy threshold `t`, so reconstruction needs t + k slots
+    pub fn new(
+        pairing_config: PairingConfig<E>,
+        committee_pks: Vec<E::G2Affine>,
+        t: usize,
+        k: usize,
+    ) -> Result<Self, PVSSError> {
+        if k == 0 {
+            return Err(PVSSError::InvalidPacking(k));
         }
+        let packed_config = PackedPVSSConfig {
+            pvss_config: PVSSConfig::new(pairing_config, committee_pks, t + k)?,
+            k,
+        };
+        packed_config.validate()?;
+        Ok(packed_config)
     }
-}
\ No newline at end of file
+
+    pub fn validate(&self) -> Result<(), PVSSError> {
+        self.pvss_config.validate()?;
+        if self.k == 0 || self.k >= self.pvss_config.t {
+            return Err(PVSSError::InvalidPacking(self.k));
+        }
+        Ok(())
+    }
+}
+
+impl<E: PairingEngine> LsssConfig<E> {
+    pub fn new(
+        pairing_config: PairingConfig<E>,
+        committee_pks: Vec<E::G2Affine>,
+        policy: &Policy,
+    ) -> Result<Self, PVSSError> {
+        let lsss_config = LsssConfig {
+            pairing_config,
+            committee_pks,
+            lsss: Lsss::from_policy(policy)?,
+        };
+        lsss_config.validate()?;
+        Ok(lsss_config)
+    }
+
+    /// Hierarchical threshold sharing, with `tiers[i]` the tier of member i
+    /// (0 is the most senior) and cumulative `thresholds` per tier
+    pub fn new_hierarchical(
+        pairing_config: PairingConfig<E>,
+        committee_pks: Vec<E::G2Affine>,
+        tiers: &[usize],
+        thresholds: &[usize],
+    ) -> Result<Self, PVSSError> {
+        if tiers.len() != committee_pks.len() {
+            return Err(PVSSError::InvalidHierarchy(format!(
+                "got {} tiers for {} members",
+                tiers.len(),
+                committee_pks.len()
+            )));
+        }
+        let lsss_config = LsssConfig {
+            pairing_config,
+            committee_pks,
+            lsss: Lsss::hierarchical(tiers, thresholds)?,
+        };
+        lsss_config.validate()?;
+        Ok(lsss_config)
+    }
+
+    pub fn validate(&self) -> Result<(), PVSSError> {
+        self.lsss.validate()?;
+        if let Some(member) = self
+            .lsss
+            .row_members
+            .iter()
+            .find(|member| **member >= self.committee_pks.len())
+        {
+            return Err(PVSSError::InvalidParticipantId(*member));
+        }
+        if self.lsss.num_rows() == 0 {
+            return Err(PVSSError::Unauthorized);
+        }
+        Ok(())
+    }
+
+    /// The public key encrypting each row
+    pub fn row_pks(&self) -> Vec<E::G2Affine> {
+        self.lsss
+            .row_members
+            .iter()
+            .map(|member| self.committee_pks[*member])
+            .collect()
+    }
+}
@@ -1,24 +1,27 @@
 #[cfg(test)]
+#[allow(clippy::module_inception)]
 mod tests {
-    use crate::{committee::*, dealer::*, public::*, structs::*};
-    use ark_bn254::Fr;
+    use crate::{committee::*, dealer::*, errors::*, public::*, structs::*, transcript::*};
+    use ark_bn254::{Bn254, Fr, G1Affine};
     use ark_ec::{AffineCurve, ProjectiveCurve};
-    use ark_ff::{PrimeField, UniformRand};
+    use ark_ff::{Field, One, PrimeField, UniformRand};
+    use rand::rngs::{StdRng, ThreadRng};
     use rand::seq::IteratorRandom;
-    use rand::thread_rng;
+    use rand::{thread_rng, SeedableRng};
+    use std::iter;
 
     #[test]
     fn share_secret() {
         let mut rng = thread_rng();
         let n: usize = 10;
         let t: usize = 5;
-        let pairing_config = PairingConfig::new();
+        let pairing_config = PairingConfig::<Bn254>::new();
         let committee_sks = (1..=n).map(|_| Fr::rand(&mut rng)).collect::<Vec<_>>();
         let committee_pks = committee_sks
             .iter()
             .map(|sk| pairing_config.h.mul(sk.into_repr()).into_affine())
             .collect::<Vec<_>>();
-        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t);
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
         let (pvss_ciphertext, pvss_secrets) = distribute_secret(&pvss_config).unwrap();
 
         verify_ciphertext(&pvss_config, &pvss_ciphertext).unwrap();
@@ -30,8 +33,7 @@ mod tests {
             .iter()
             .map(|i| {
                 let share = decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap();
-                let share2 = share.clone();
-                verify_share(&pvss_config, &pvss_ciphertext, share2, *i).unwrap();
+                verify_share(&pvss_config, &pvss_ciphertext, share, *i).unwrap();
                 share
             })
             .collect::<Vec<_>>();
@@ -46,8 +48,7 @@ mod tests {
             .iter()
             .map(|i| {
                 let share = decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap();
-                let share2 = share.clone();
-                verify_share(&pvss_config, &pvss_ciphertext, share2, *i).unwrap();
+                verify_share(&pvss_config, &pvss_ciphertext, share, *i).unwrap();
                 share
             })
             .collect::<Vec<_>>();
@@ -62,8 +63,7 @@ mod tests {
             .iter()
             .map(|i| {
                 let share = decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap();
-                let share2 = share.clone();
-                verify_share(&pvss_config, &pvss_ciphertext, share2, *i).unwrap();
+                verify_share(&pvss_config, &pvss_ciphertext, share, *i).unwrap();
                 share
             })
             .collect::<Vec<_>>();
@@ -79,8 +79,1473 @@ mod tests {
         let alpha = Fr::rand(&mut rng);
         let x = (1..=n).map(|i| Fr::from(i as i64)).collect::<Vec<_>>();
         assert_eq!(
-            gen_lagrange_coefficients(x, alpha),
+            gen_lagrange_coefficients(x.clone(), alpha),
             gen_all_lagrange_coefficients(n, alpha)
         );
+        assert_eq!(
+            gen_lagrange_coefficients(x, Fr::from(3)),
+            gen_all_lagrange_coefficients(n, Fr::from(3))
+        );
+        assert!(gen_all_lagrange_coefficients(0, alpha).is_empty());
+    }
+
+    #[test]
+    fn seeded_rng_is_reproducible() {
+        let n = 6;
+        let t = 4;
+        let deal = |seed: u64| {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let pairing_config = PairingConfig::<Bn254>::new();
+            let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+                .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+                .unzip();
+            let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+            let (pvss_ciphertext, pvss_secrets) =
+                distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+            verify_ciphertext(&pvss_config, &pvss_ciphertext).unwrap();
+            (committee_sks, pvss_ciphertext, pvss_secrets)
+        };
+
+        let (sks_a, ciphertext_a, secrets_a) = deal(42);
+        let (sks_b, ciphertext_b, secrets_b) = deal(42);
+        assert_eq!(sks_a, sks_b);
+        assert_eq!(ciphertext_a.f_i, ciphertext_b.f_i);
+        assert_eq!(ciphertext_a.a_i, ciphertext_b.a_i);
+        assert_eq!(ciphertext_a.y_i, ciphertext_b.y_i);
+        assert_eq!(secrets_a.f_0, secrets_b.f_0);
+
+        let (_, _, secrets_c) = deal(43);
+        assert_ne!(secrets_a.f_0, secrets_c.f_0);
+    }
+
+    #[test]
+    fn challenge_matches_contract_vector() {
+        use crate::serialize::*;
+        use serde::Deserialize;
+
+        // NOTE: test/test_pvss_lib.ts asserts the same challenge from PVSSLib.genChallenge
+        #[derive(Deserialize)]
+        struct Vector {
+            n: usize,
+            t: usize,
+            pks: Vec<G2AffineSerializable>,
+            ciphertext: PVSSCiphertext,
+            challenge: FrSerializable,
+        }
+        let vector: Vector = serde_json::from_str(include_str!(
+            "../../test/vectors/verify_distribution_challenge.json"
+        ))
+        .unwrap();
+        let pks = vector.pks.into_iter().map(|pk| pk.0).collect::<Vec<_>>();
+        assert_eq!(pks.len(), vector.n);
+        let pvss_config = PVSSConfig::new(PairingConfig::new(), pks, vector.t).unwrap();
+        verify_ciphertext(&pvss_config, &vector.ciphertext).unwrap();
+        assert_eq!(
+            verify_distribution_challenge(&pvss_config, &vector.ciphertext),
+            vector.challenge.0
+        );
+    }
+
+    #[test]
+    fn challenge_binds_transcript() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let committee_pks = (0..5)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng).1)
+            .collect::<Vec<_>>();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, 3).unwrap();
+        let (mut pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        let alpha = verify_distribution_challenge(&pvss_config, &pvss_ciphertext);
+        assert_eq!(
+            alpha,
+            verify_distribution_challenge(&pvss_config, &pvss_ciphertext)
+        );
+
+        pvss_ciphertext.y_i.swap(0, 1);
+        assert_ne!(
+            alpha,
+            verify_distribution_challenge(&pvss_config, &pvss_ciphertext)
+        );
+        assert!(verify_ciphertext(&pvss_config, &pvss_ciphertext).is_err());
+    }
+
+    #[test]
+    fn reconstruct_drops_bad_shares() {
+        let mut rng = thread_rng();
+        let n = 10;
+        let t = 5;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        let mut shares = (0..n)
+            .map(|i| {
+                let share = decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap();
+                (i, share)
+            })
+            .collect::<Vec<_>>();
+        let garbage = pvss_config
+            .pairing_config
+            .h
+            .mul(Fr::rand(&mut rng).into_repr())
+            .into_affine();
+        shares[2].1 = garbage;
+        shares[7].1 = garbage;
+        shares.push((n + 3, garbage));
+
+        let reconstruction = reconstruct(&pvss_config, &pvss_ciphertext, &shares).unwrap();
+        assert_eq!(reconstruction.secret, pvss_secrets.h_f_0);
+        let mut rejected = reconstruction.rejected;
+        rejected.sort();
+        assert_eq!(rejected, vec![2, 7, n + 3]);
+
+        match reconstruct(&pvss_config, &pvss_ciphertext, &shares[..5]) {
+            Err(PVSSError::NotEnoughShares { have: 4, need: 5 }) => {}
+            _ => panic!("expected NotEnoughShares"),
+        }
+
+        // Malformed input fails before any share is checked
+        let bad_config = PVSSConfig {
+            pairing_config: PairingConfig::new(),
+            committee_pks: pvss_config.committee_pks.clone(),
+            weights: vec![1; n],
+            t: 0,
+        };
+        assert!(matches!(
+            reconstruct(&bad_config, &pvss_ciphertext, &shares),
+            Err(PVSSError::InvalidThreshold { t: 0, n: 10 })
+        ));
+        let mut truncated = pvss_ciphertext;
+        truncated.a_i.pop();
+        assert!(matches!(
+            reconstruct(&pvss_config, &truncated, &shares),
+            Err(PVSSError::InvalidCiphertextShape { field: "a_i", .. })
+        ));
+    }
+
+    #[test]
+    fn batch_verify_shares() {
+        let mut rng = thread_rng();
+        let n = 20;
+        let t = 7;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        let mut shares = (0..n)
+            .map(|i| {
+                let share = decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap();
+                (i, share)
+            })
+            .collect::<Vec<_>>();
+        verify_shares_batch(&pvss_config, &pvss_ciphertext, &shares).unwrap();
+        verify_shares_batch(&pvss_config, &pvss_ciphertext, &[]).unwrap();
+
+        shares[3].1 = shares[4].1;
+        assert!(verify_shares_batch(&pvss_config, &pvss_ciphertext, &shares).is_err());
+
+        match verify_shares_batch(&pvss_config, &pvss_ciphertext, &[(n, shares[0].1)]) {
+            Err(PVSSError::InvalidParticipantId(i)) => assert_eq!(i, n),
+            _ => panic!("expected InvalidParticipantId"),
+        }
+    }
+
+    #[cfg(feature = "bls12_381")]
+    #[test]
+    fn share_secret_bls12_381() {
+        use crate::serialize::G2AffineSerializable;
+        use ark_bls12_381::Bls12_381;
+
+        let mut rng = thread_rng();
+        let n = 7;
+        let t = 4;
+        let pairing_config = PairingConfig::<Bls12_381>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        verify_ciphertext(&pvss_config, &pvss_ciphertext).unwrap();
+
+        let json = serde_json::to_string(&pvss_ciphertext).unwrap();
+        let pvss_ciphertext: PVSSCiphertext<Bls12_381> = serde_json::from_str(&json).unwrap();
+        verify_ciphertext(&pvss_config, &pvss_ciphertext).unwrap();
+
+        let shares = (0..t)
+            .map(|i| {
+                let share = decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap();
+                (i, share)
+            })
+            .collect::<Vec<_>>();
+        let reconstruction = reconstruct(&pvss_config, &pvss_ciphertext, &shares).unwrap();
+        assert_eq!(reconstruction.secret, pvss_secrets.h_f_0);
+
+        let json = serde_json::to_string(&G2AffineSerializable::<Bls12_381>(reconstruction.secret));
+        let secret: G2AffineSerializable<Bls12_381> = serde_json::from_str(&json.unwrap()).unwrap();
+        assert_eq!(secret.0, pvss_secrets.h_f_0);
+    }
+
+    #[test]
+    fn deserialization_validates_points() {
+        use crate::serialize::*;
+        use ark_bn254::{Fq, Fq2, G2Affine};
+
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (_, pk) = gen_keypair_with_rng(&pairing_config, &mut rng);
+        let json = serde_json::to_string(&G2AffineSerializable::<Bn254>(pk)).unwrap();
+        let parsed: G2AffineSerializable = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.0, pk);
+
+        // Off the curve
+        let mut off_curve = pk;
+        off_curve.y.c0 += Fq::one();
+        let json = serde_json::to_string(&G2AffineSerializable::<Bn254>(off_curve)).unwrap();
+        let err = serde_json::from_str::<G2AffineSerializable>(&json)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("not on the curve"));
+        let json = serde_json::to_string(&G1AffineSerializable::<Bn254>(G1Affine::new(
+            pairing_config.g.x,
+            pairing_config.g.x,
+            false,
+        )))
+        .unwrap();
+        let err = serde_json::from_str::<G1AffineSerializable>(&json)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("not on the curve"));
+
+        // On the curve, but outside the prime-order subgroup
+        let outside_subgroup = iter::repeat_with(|| Fq2::rand(&mut rng))
+            .find_map(|x| G2Affine::get_point_from_x(x, false))
+            .unwrap();
+        assert!(!outside_subgroup.is_in_correct_subgroup_assuming_on_curve());
+        let json = serde_json::to_string(&G2AffineSerializable::<Bn254>(outside_subgroup)).unwrap();
+        let err = serde_json::from_str::<G2AffineSerializable>(&json)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("subgroup"));
+
+        // Unchecked fast path for trusted input
+        let parsed: UncheckedG2AffineSerializable = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.0, outside_subgroup);
+
+        // Malformed hex is an error, never a panic
+        for bad in ["", "0", "0x", "x\u{e9}", "\u{e9}0x01", "0xzz"] {
+            let err = serde_json::from_value::<FrSerializable>(bad.into())
+                .err()
+                .unwrap();
+            assert!(err.to_string().contains("Invalid hex"));
+        }
+        let too_large = format!("0x{}", "ff".repeat(32));
+        let err = serde_json::from_value::<FrSerializable>(too_large.into())
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("not a canonical field element"));
+    }
+
+    #[test]
+    fn point_at_infinity_round_trips() {
+        use crate::serialize::*;
+        use ark_bn254::G2Affine;
+        use ark_ff::Zero;
+
+        // alt_bn128.sol represents the identity as (0, 0)
+        let zero = format!("\"0x{}\"", "0".repeat(64));
+        let g1_json = format!("{{\"x\":{zero},\"y\":{zero}}}");
+        let g2_json = format!("{{\"x\":[{zero},{zero}],\"y\":[{zero},{zero}]}}");
+
+        assert_eq!(
+            serde_json::to_string(&G1AffineSerializable::<Bn254>(G1Affine::zero())).unwrap(),
+            g1_json
+        );
+        assert_eq!(
+            serde_json::to_string(&G2AffineSerializable::<Bn254>(G2Affine::zero())).unwrap(),
+            g2_json
+        );
+        let g1: G1AffineSerializable = serde_json::from_str(&g1_json).unwrap();
+        assert!(g1.0.is_zero());
+        let g2: G2AffineSerializable = serde_json::from_str(&g2_json).unwrap();
+        assert!(g2.0.is_zero());
+        let g1: UncheckedG1AffineSerializable = serde_json::from_str(&g1_json).unwrap();
+        assert!(g1.0.is_zero());
+        let g2: UncheckedG2AffineSerializable = serde_json::from_str(&g2_json).unwrap();
+        assert!(g2.0.is_zero());
+
+        // A dealing with a zero coefficient survives the JSON codec
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let committee_pks = (0..4)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng).1)
+            .collect::<Vec<_>>();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, 3).unwrap();
+        let (mut pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        pvss_ciphertext.f_i[1] = G1Affine::zero();
+        pvss_ciphertext.y_i[2] = G2Affine::zero();
+        let json = serde_json::to_string(&pvss_ciphertext).unwrap();
+        let parsed: PVSSCiphertext = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.f_i, pvss_ciphertext.f_i);
+        assert_eq!(parsed.y_i, pvss_ciphertext.y_i);
+        assert_eq!(
+            verify_distribution_challenge(&pvss_config, &parsed),
+            verify_distribution_challenge(&pvss_config, &pvss_ciphertext)
+        );
+    }
+
+    #[test]
+    fn seal_and_open() {
+        use crate::seal::*;
+
+        let mut rng = thread_rng();
+        let n: usize = 5;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        let payload = (0..10_000).map(|i| (i % 251) as u8).collect::<Vec<_>>();
+        let envelope = seal(&pvss_ciphertext, &pvss_secrets.h_f_0, &payload).unwrap();
+        assert_eq!(
+            envelope.ciphertext_hash,
+            ciphertext_digest(&pvss_ciphertext)
+        );
+
+        // The committee recovers the key from t shares and the envelope survives JSON
+        let indices = (0..n).choose_multiple(&mut rng, t);
+        let shares = indices
+            .iter()
+            .map(|i| decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap())
+            .collect::<Vec<_>>();
+        let secret = combine_shares(&shares, &indices).unwrap();
+        let envelope: SealedEnvelope =
+            serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+        assert_eq!(open::<Bn254>(&secret, &envelope).unwrap(), payload);
+
+        // Wrong secret, tampered payload or rebinding to another dealing all fail
+        let wrong_secret = combine_shares(&shares[..t - 1], &indices[..t - 1]).unwrap();
+        assert!(matches!(
+            open::<Bn254>(&wrong_secret, &envelope),
+            Err(PVSSError::OpenError)
+        ));
+        let mut tampered: SealedEnvelope =
+            serde_json::from_str(&serde_json::to_string(&envelope).unwrap()).unwrap();
+        tampered.payload[0] ^= 1;
+        assert!(open::<Bn254>(&secret, &tampered).is_err());
+        let mut rebound = tampered;
+        rebound.payload[0] ^= 1;
+        rebound.ciphertext_hash[0] ^= 1;
+        assert!(open::<Bn254>(&secret, &rebound).is_err());
+    }
+
+    #[test]
+    fn kem_encrypt_to_dealing() {
+        use crate::kem::*;
+
+        let mut rng = thread_rng();
+        let n: usize = 5;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        // Several third parties encrypt to the same dealing
+        let messages: [&[u8]; 2] = [b"first message", b"second message"];
+        let kem_ciphertexts = messages
+            .iter()
+            .map(|m| kem_encrypt(&pvss_config.pairing_config, &pvss_ciphertext.f_i[0], m).unwrap())
+            .collect::<Vec<_>>();
+        assert_ne!(kem_ciphertexts[0].u, kem_ciphertexts[1].u);
+
+        let indices = (0..n).choose_multiple(&mut rng, t);
+        let shares = indices
+            .iter()
+            .map(|i| decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap())
+            .collect::<Vec<_>>();
+        let secret = combine_shares(&shares, &indices).unwrap();
+        for (m, c) in messages.iter().zip(kem_ciphertexts.iter()) {
+            let c: KemCiphertext =
+                serde_json::from_str(&serde_json::to_string(c).unwrap()).unwrap();
+            assert_eq!(kem_decrypt(&secret, &c).unwrap(), *m);
+        }
+
+        let wrong_secret = combine_shares(&shares[..t - 1], &indices[..t - 1]).unwrap();
+        assert!(matches!(
+            kem_decrypt(&wrong_secret, &kem_ciphertexts[0]),
+            Err(PVSSError::OpenError)
+        ));
+
+        // A refresh dealing commits to f_0 = 0, which would leave the key public
+        let refresh = distribute_refresh_with_rng(&pvss_config, &mut rng).unwrap();
+        assert!(matches!(
+            kem_encrypt(&pvss_config.pairing_config, &refresh.f_i[0], b"leaked"),
+            Err(PVSSError::IdentityCommitment)
+        ));
+    }
+
+    #[test]
+    fn threshold_kem_decryption() {
+        use crate::kem::*;
+
+        let mut rng = thread_rng();
+        let n: usize = 5;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let pairing_config = &pvss_config.pairing_config;
+        let (pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        let kem_ciphertext = kem_encrypt(
+            pairing_config,
+            &pvss_ciphertext.f_i[0],
+            b"threshold payload",
+        )
+        .unwrap();
+
+        let indices = (0..n).choose_multiple(&mut rng, t);
+        let partial_decryptions = indices
+            .iter()
+            .map(|i| {
+                let share = decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap();
+                let pd = partial_decrypt(
+                    pairing_config,
+                    &pvss_ciphertext,
+                    &kem_ciphertext,
+                    &share,
+                    *i,
+                )
+                .unwrap();
+                verify_partial_decryption(pairing_config, &pvss_ciphertext, &kem_ciphertext, &pd)
+                    .unwrap();
+                pd
+            })
+            .collect::<Vec<_>>();
+
+        let shared = combine_partial_decryptions(&pvss_config, &partial_decryptions).unwrap();
+        assert_eq!(
+            kem_decrypt_shared(&shared, &kem_ciphertext).unwrap(),
+            b"threshold payload"
+        );
+        assert!(matches!(
+            combine_partial_decryptions(&pvss_config, &partial_decryptions[..t - 1]),
+            Err(PVSSError::NotEnoughShares { have: 2, need: 3 })
+        ));
+        let first = &partial_decryptions[0];
+        let repeated = partial_decryptions[..t - 1]
+            .iter()
+            .chain(iter::once(first))
+            .map(|pd| PartialDecryption {
+                i: pd.i,
+                d_i: pd.d_i,
+                c: pd.c,
+                z: pd.z,
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            combine_partial_decryptions(&pvss_config, &repeated),
+            Err(PVSSError::DuplicateIndex(i)) if i == first.i
+        ));
+
+        // A partial decryption claimed for another index, or with a tampered d_i, is rejected
+        let i = indices[0];
+        let share = decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap();
+        let mut pd =
+            partial_decrypt(pairing_config, &pvss_ciphertext, &kem_ciphertext, &share, i).unwrap();
+        pd.i = (i + 1) % n;
+        assert!(matches!(
+            verify_partial_decryption(pairing_config, &pvss_ciphertext, &kem_ciphertext, &pd),
+            Err(PVSSError::InvalidPartialDecryption(_))
+        ));
+        pd.i = i;
+        pd.d_i = pd.d_i * pd.d_i;
+        assert!(
+            verify_partial_decryption(pairing_config, &pvss_ciphertext, &kem_ciphertext, &pd)
+                .is_err()
+        );
+
+        // KEM ciphertexts aimed at another dealing are refused
+        let (other_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        let pd = &partial_decryptions[0];
+        assert!(matches!(
+            verify_partial_decryption(pairing_config, &other_ciphertext, &kem_ciphertext, pd),
+            Err(PVSSError::CommitmentMismatch)
+        ));
+    }
+
+    #[test]
+    fn deal_given_secret() {
+        let mut rng = thread_rng();
+        let n: usize = 5;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+
+        // Largest secret the circuit accepts
+        let secret = Fr::from(2u64).pow([MAX_SECRET_BITS as u64]) - Fr::one();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_given_secret(&pvss_config, secret).unwrap();
+        assert_eq!(pvss_secrets.f_0, secret);
+        verify_ciphertext(&pvss_config, &pvss_ciphertext).unwrap();
+
+        let indices = (0..n).choose_multiple(&mut rng, t);
+        let shares = indices
+            .iter()
+            .map(|i| decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            combine_shares(&shares, &indices).unwrap(),
+            pvss_config.pairing_config.h.mul(secret.into_repr())
+        );
+
+        let too_large = secret + Fr::one();
+        assert!(matches!(
+            distribute_given_secret(&pvss_config, too_large),
+            Err(PVSSError::SecretTooLarge {
+                bits: 251,
+                max: 250
+            })
+        ));
+    }
+
+    #[test]
+    fn refresh_shares() {
+        let mut rng = thread_rng();
+        let n: usize = 6;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        // Two members each deal a refresh
+        let refreshes = (0..2)
+            .map(|_| distribute_refresh_with_rng(&pvss_config, &mut rng).unwrap())
+            .collect::<Vec<_>>();
+        let refreshed = refresh_ciphertext(&pvss_ciphertext, &refreshes).unwrap();
+        verify_refreshed_ciphertext(&pvss_config, &pvss_ciphertext, &refreshes, &refreshed)
+            .unwrap();
+        verify_ciphertext(&pvss_config, &refreshed).unwrap();
+        assert_eq!(refreshed.f_i[0], pvss_ciphertext.f_i[0]);
+
+        let decrypt_all = |ciphertext: &PVSSCiphertext| {
+            (0..n)
+                .map(|i| decrypt_share(ciphertext, &committee_sks[i], i).unwrap())
+                .collect::<Vec<_>>()
+        };
+        let old_shares = decrypt_all(&pvss_ciphertext);
+        let new_shares = decrypt_all(&refreshed);
+        assert_eq!(
+            combine_shares(&new_shares[..t], &(0..t).collect::<Vec<_>>()).unwrap(),
+            pvss_secrets.h_f_0
+        );
+
+        // Mixing old and refreshed shares does not reconstruct
+        let mixed = [old_shares[0], old_shares[1], new_shares[2]];
+        assert_ne!(
+            combine_shares(&mixed, &[0, 1, 2]).unwrap(),
+            pvss_secrets.h_f_0
+        );
+
+        // A refresh that changes the secret, or a refreshed ciphertext that skips one, is rejected
+        let (bad_refresh, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        assert!(matches!(
+            verify_refresh(&pvss_config, &bad_refresh),
+            Err(PVSSError::RefreshSecretNotZero)
+        ));
+        let partial = refresh_ciphertext(&pvss_ciphertext, &refreshes[..1]).unwrap();
+        assert!(matches!(
+            verify_refreshed_ciphertext(&pvss_config, &pvss_ciphertext, &refreshes, &partial),
+            Err(PVSSError::RefreshMismatch)
+        ));
+    }
+
+    #[test]
+    fn distributed_key_generation() {
+        use crate::dkg::*;
+        use ark_ec::PairingEngine;
+
+        let mut rng = thread_rng();
+        let n: usize = 5;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+
+        let contribute = |session: &[u8], dealers: &[usize], rng: &mut ThreadRng| {
+            dealers
+                .iter()
+                .map(|&dealer| {
+                    let sk = &committee_sks[dealer];
+                    deal_contribution_with_rng(&pvss_config, session, dealer, sk, rng).unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        let session = b"dkg-1";
+        let mut contributions = contribute(session, &[0, 1, 2, 3, 4], &mut rng);
+        for (ciphertext, proof) in contributions.iter() {
+            verify_contribution(&pvss_config, session, ciphertext, proof).unwrap();
+        }
+
+        // Contributions from an earlier run with the same committee do not verify again
+        let replayed = contribute(b"dkg-0", &[0, 1, 2], &mut rng);
+        for (ciphertext, proof) in replayed.iter() {
+            assert!(matches!(
+                verify_contribution(&pvss_config, session, ciphertext, proof),
+                Err(PVSSError::InvalidContribution(_))
+            ));
+        }
+        assert!(matches!(
+            complete_dkg(&pvss_config, session, replayed),
+            Err(PVSSError::NotEnoughContributions { have: 0, need: 3 })
+        ));
+
+        // A proof cannot be moved to another dealing with the same f_0 commitment
+        let (ciphertext, proof) = &contributions[1];
+        let mut other = distribute_refresh_with_rng(&pvss_config, &mut rng).unwrap();
+        other.f_i[0] = ciphertext.f_i[0];
+        assert!(matches!(
+            verify_contribution(&pvss_config, session, &other, proof),
+            Err(PVSSError::InvalidContribution(1))
+        ));
+
+        // Two valid contributions from one dealer are an error, not a silent choice
+        let twice = contribute(session, &[0, 1, 2, 2], &mut rng);
+        assert!(matches!(
+            complete_dkg(&pvss_config, session, twice),
+            Err(PVSSError::DuplicateDealer(2))
+        ));
+
+        // Dealer 3 copies dealer 0's commitment without knowing its f_0
+        contributions[3].1.commitment = contributions[0].1.commitment;
+        contributions[3].0.f_i[0] = contributions[0].1.commitment;
+        let (aggregated, rejected) = complete_dkg(&pvss_config, session, contributions).unwrap();
+        assert_eq!(rejected, vec![3]);
+        verify_aggregated_ciphertext(&pvss_config, session, &aggregated).unwrap();
+
+        // The committee can reconstruct h^{f_0} for the aggregated f_0
+        let indices = (0..n).choose_multiple(&mut rng, t);
+        let shares = indices
+            .iter()
+            .map(|i| decrypt_share(&aggregated.ciphertext, &committee_sks[*i], *i).unwrap())
+            .collect::<Vec<_>>();
+        let secret = combine_shares(&shares, &indices).unwrap();
+        let g = pvss_config.pairing_config.g;
+        let h = pvss_config.pairing_config.h;
+        assert_eq!(
+            Bn254::pairing(aggregated.ciphertext.f_i[0], h),
+            Bn254::pairing(g, secret)
+        );
+
+        // Dropping a proof leaves the aggregated f_0 unexplained
+        let mut short = AggregatedCiphertext {
+            ciphertext: aggregated.ciphertext,
+            proofs: aggregated.proofs.clone(),
+        };
+        short.proofs.pop();
+        assert!(matches!(
+            verify_aggregated_ciphertext(&pvss_config, session, &short),
+            Err(PVSSError::AggregateMismatch)
+        ));
+        short.proofs.pop();
+        assert!(matches!(
+            verify_aggregated_ciphertext(&pvss_config, session, &short),
+            Err(PVSSError::NotEnoughContributions { have: 2, need: 3 })
+        ));
+
+        // Member 4 cannot contribute under other members' ids
+        assert!(matches!(
+            deal_contribution_with_rng(&pvss_config, session, 0, &committee_sks[4], &mut rng),
+            Err(PVSSError::InvalidSecretKeyError)
+        ));
+        let (ciphertext, mut proof) =
+            deal_contribution_with_rng(&pvss_config, session, 4, &committee_sks[4], &mut rng)
+                .unwrap();
+        proof.dealer = 0;
+        assert!(matches!(
+            verify_contribution(&pvss_config, session, &ciphertext, &proof),
+            Err(PVSSError::InvalidContribution(0))
+        ));
+        proof.pk = pvss_config.committee_pks[0];
+        assert!(matches!(
+            verify_contribution(&pvss_config, session, &ciphertext, &proof),
+            Err(PVSSError::InvalidContribution(0))
+        ));
+
+        // A key registered twice still counts as one dealer
+        let repeated = PVSSConfig::new(
+            PairingConfig::<Bn254>::new(),
+            vec![pvss_config.committee_pks[0]; 3],
+            2,
+        )
+        .unwrap();
+        let contributions = (0..3)
+            .map(|dealer| {
+                deal_contribution_with_rng(&repeated, session, dealer, &committee_sks[0], &mut rng)
+                    .unwrap()
+            })
+            .collect::<Vec<_>>();
+        assert!(matches!(
+            complete_dkg(&repeated, session, contributions),
+            Err(PVSSError::NotEnoughContributions { have: 1, need: 2 })
+        ));
+    }
+
+    #[test]
+    fn committee_proofs_of_possession() {
+        use ark_bn254::G2Affine;
+        use ark_ff::Zero;
+
+        let mut rng = thread_rng();
+        let n: usize = 4;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, mut committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let mut pops = committee_sks
+            .iter()
+            .zip(committee_pks.iter())
+            .map(|(sk, pk)| prove_possession_with_rng(&pairing_config, sk, pk, &mut rng))
+            .collect::<Vec<_>>();
+        verify_committee_pops(&pairing_config, &committee_pks, &pops).unwrap();
+        PVSSConfig::new_with_pops(PairingConfig::new(), committee_pks.clone(), &pops, 2).unwrap();
+
+        // A rogue key derived from member 0's key, reusing a proof it cannot produce
+        let rogue_pk =
+            (committee_pks[0].into_projective() + pairing_config.h.into_projective()).into_affine();
+        let honest_pk = std::mem::replace(&mut committee_pks[2], rogue_pk);
+        assert!(matches!(
+            PVSSConfig::new_with_pops(PairingConfig::new(), committee_pks.clone(), &pops, 2),
+            Err(PVSSError::InvalidProofOfPossession(2))
+        ));
+        committee_pks[2] = honest_pk;
+
+        // Missing or extra proofs and the identity key are rejected too
+        assert!(matches!(
+            verify_committee_pops(&pairing_config, &committee_pks, &pops[..3]),
+            Err(PVSSError::PopCountMismatch {
+                pops: 3,
+                members: 4
+            })
+        ));
+        assert!(matches!(
+            verify_committee_pops(&pairing_config, &committee_pks[..3], &pops),
+            Err(PVSSError::PopCountMismatch {
+                pops: 4,
+                members: 3
+            })
+        ));
+        committee_pks[1] = G2Affine::zero();
+        pops[1] = prove_possession(&pairing_config, &Fr::zero(), &committee_pks[1]);
+        assert!(matches!(
+            verify_committee_pops(&pairing_config, &committee_pks, &pops),
+            Err(PVSSError::InvalidProofOfPossession(1))
+        ));
+    }
+
+    #[test]
+    fn malformed_input_returns_errors() {
+        let mut rng = thread_rng();
+        let n: usize = 4;
+        let t: usize = 2;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+
+        for bad_t in [0, n + 1] {
+            assert!(matches!(
+                PVSSConfig::new(PairingConfig::<Bn254>::new(), committee_pks.clone(), bad_t),
+                Err(PVSSError::InvalidThreshold { t, n: 4 }) if t == bad_t
+            ));
+        }
+        let mut pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        // Out-of-range participants
+        assert!(matches!(
+            decrypt_share(&pvss_ciphertext, &committee_sks[0], n),
+            Err(PVSSError::InvalidParticipantId(4))
+        ));
+        let share = decrypt_share(&pvss_ciphertext, &committee_sks[0], 0).unwrap();
+        assert!(matches!(
+            verify_share(&pvss_config, &pvss_ciphertext, share, n),
+            Err(PVSSError::InvalidParticipantId(4))
+        ));
+        assert!(matches!(
+            combine_shares(&[share], &[0, 1]),
+            Err(PVSSError::ShareCountMismatch {
+                shares: 1,
+                indices: 2
+            })
+        ));
+
+        // Ciphertexts whose shape does not match the config
+        let truncated = |f: usize, a: usize, y: usize| PVSSCiphertext::<Bn254> {
+            f_i: pvss_ciphertext.f_i[..f].to_vec(),
+            a_i: pvss_ciphertext.a_i[..a].to_vec(),
+            y_i: pvss_ciphertext.y_i[..y].to_vec(),
+        };
+        assert!(matches!(
+            verify_ciphertext(&pvss_config, &truncated(1, n, n)),
+            Err(PVSSError::InvalidCiphertextShape {
+                field: "f_i",
+                expected: 2,
+                actual: 1
+            })
+        ));
+        assert!(matches!(
+            verify_ciphertext(&pvss_config, &truncated(t, n - 1, n)),
+            Err(PVSSError::InvalidCiphertextShape { field: "a_i", .. })
+        ));
+        assert!(matches!(
+            verify_ciphertext(&pvss_config, &truncated(t, n, 0)),
+            Err(PVSSError::InvalidCiphertextShape { field: "y_i", .. })
+        ));
+
+        // Configs built by hand are re-checked at the entry points
+        pvss_config.t = 0;
+        assert!(matches!(
+            distribute_secret(&pvss_config),
+            Err(PVSSError::InvalidThreshold { t: 0, n: 4 })
+        ));
+        assert!(matches!(
+            verify_ciphertext(&pvss_config, &pvss_ciphertext),
+            Err(PVSSError::InvalidThreshold { .. })
+        ));
+    }
+
+    #[test]
+    fn reconstruct_secret_checks_threshold() {
+        let mut rng = thread_rng();
+        let n: usize = 5;
+        let t: usize = 3;
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..n)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, t).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        let shares = (0..n)
+            .map(|i| {
+                (
+                    i,
+                    decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+
+        let secret =
+            reconstruct_secret(&pvss_config, Some(&pvss_ciphertext), &shares[1..4]).unwrap();
+        assert_eq!(secret, pvss_secrets.h_f_0);
+
+        assert!(matches!(
+            reconstruct_secret(&pvss_config, None, &shares[..t - 1]),
+            Err(PVSSError::NotEnoughShares { have: 2, need: 3 })
+        ));
+        let duplicated = [shares[0], shares[1], shares[1]];
+        assert!(matches!(
+            reconstruct_secret(&pvss_config, None, &duplicated),
+            Err(PVSSError::DuplicateIndex(1))
+        ));
+        let out_of_range = [shares[0], shares[1], (n, shares[2].1)];
+        assert!(matches!(
+            reconstruct_secret(&pvss_config, None, &out_of_range),
+            Err(PVSSError::InvalidParticipantId(5))
+        ));
+
+        // A share from the wrong member passes the count checks but not the pairing check
+        let wrong = [shares[0], shares[1], (2, shares[3].1)];
+        assert!(reconstruct_secret(&pvss_config, None, &wrong).is_ok());
+        assert!(matches!(
+            reconstruct_secret(&pvss_config, Some(&pvss_ciphertext), &wrong),
+            Err(PVSSError::SecretMismatch)
+        ));
+    }
+
+    #[test]
+    fn verify_secret_against_dealing() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let committee_pks = (0..3)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng).1)
+            .collect::<Vec<_>>();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, 2).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        let (other_ciphertext, other_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        verify_secret(&pvss_config, &pvss_ciphertext, &pvss_secrets.h_f_0).unwrap();
+        verify_secret_scalar(&pvss_config, &pvss_ciphertext, &pvss_secrets.f_0).unwrap();
+        assert!(matches!(
+            verify_secret(&pvss_config, &pvss_ciphertext, &other_secrets.h_f_0),
+            Err(PVSSError::SecretMismatch)
+        ));
+        assert!(matches!(
+            verify_secret_scalar(&pvss_config, &other_ciphertext, &pvss_secrets.f_0),
+            Err(PVSSError::SecretMismatch)
+        ));
+        let mut truncated = other_ciphertext;
+        truncated.f_i.pop();
+        assert!(matches!(
+            verify_secret(&pvss_config, &truncated, &other_secrets.h_f_0),
+            Err(PVSSError::InvalidCiphertextShape { field: "f_i", .. })
+        ));
+    }
+
+    #[test]
+    fn weighted_sharing() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..3)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let weights = vec![3, 1, 2];
+        let pvss_config =
+            PVSSConfig::new_weighted(pairing_config, committee_pks.clone(), weights, 4).unwrap();
+        assert_eq!(pvss_config.num_slots(), 6);
+        assert_eq!(pvss_config.slots(2), Some(4..6));
+        assert_eq!(pvss_config.member_of_slot(3), Some(1));
+        assert_eq!(pvss_config.member_of_slot(6), None);
+
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        verify_ciphertext(&pvss_config, &pvss_ciphertext).unwrap();
+
+        // Same transcript as registering each pk once per slot
+        let repeated =
+            PVSSConfig::new(PairingConfig::new(), pvss_config.slot_pks(), pvss_config.t).unwrap();
+        assert_eq!(
+            verify_distribution_challenge(&pvss_config, &pvss_ciphertext),
+            verify_distribution_challenge(&repeated, &pvss_ciphertext)
+        );
+
+        let member_shares = |member: usize| {
+            decrypt_member_shares(
+                &pvss_config,
+                &pvss_ciphertext,
+                &committee_sks[member],
+                member,
+            )
+            .unwrap()
+        };
+        let heavy = member_shares(0);
+        assert_eq!(heavy.len(), 3);
+        assert!(matches!(
+            reconstruct_secret(&pvss_config, None, &heavy),
+            Err(PVSSError::NotEnoughShares { have: 3, need: 4 })
+        ));
+        let shares = [heavy, member_shares(1)].concat();
+        assert_eq!(
+            reconstruct_secret(&pvss_config, Some(&pvss_ciphertext), &shares).unwrap(),
+            pvss_secrets.h_f_0
+        );
+        assert!(matches!(
+            decrypt_member_shares(&pvss_config, &pvss_ciphertext, &committee_sks[0], 3),
+            Err(PVSSError::InvalidParticipantId(3))
+        ));
+
+        assert!(matches!(
+            PVSSConfig::new_weighted(
+                PairingConfig::<Bn254>::new(),
+                committee_pks.clone(),
+                vec![1, 0, 1],
+                1
+            ),
+            Err(PVSSError::InvalidWeight(1))
+        ));
+        assert!(matches!(
+            PVSSConfig::new_weighted(PairingConfig::<Bn254>::new(), committee_pks, vec![1, 1], 1),
+            Err(PVSSError::WeightCountMismatch {
+                weights: 2,
+                members: 3
+            })
+        ));
+    }
+
+    #[test]
+    fn lsss_policies() {
+        use crate::lsss::*;
+
+        assert_eq!(
+            Policy::parse("2 of (0, 1, 2) AND (3 or 4)").unwrap(),
+            Policy::Threshold {
+                k: 2,
+                children: vec![
+                    Policy::Threshold {
+                        k: 2,
+                        children: (0..3).map(Policy::Member).collect(),
+                    },
+                    Policy::Threshold {
+                        k: 1,
+                        children: vec![Policy::Member(3), Policy::Member(4)],
+                    },
+                ],
+            }
+        );
+        for bad in ["", "0 and", "2 of (0, 1", "0 xor 1", "1 # 2", "0 1"] {
+            assert!(matches!(
+                Policy::parse(bad),
+                Err(PVSSError::PolicyParseError(_))
+            ));
+        }
+        assert!(matches!(
+            Lsss::<Fr>::from_policy(&Policy::parse("3 of (0, 1)").unwrap()),
+            Err(PVSSError::InvalidThreshold { t: 3, n: 2 })
+        ));
+
+        // Nesting is capped instead of overflowing the stack
+        let nested = |depth: usize| format!("{}0{}", "(".repeat(depth), ")".repeat(depth));
+        Policy::parse(&nested(MAX_POLICY_DEPTH - 1)).unwrap();
+        assert!(matches!(
+            Policy::parse(&nested(100_000)),
+            Err(PVSSError::PolicyParseError(_))
+        ));
+        let deep = (0..MAX_POLICY_DEPTH).fold(Policy::Member(0), |child, _| Policy::Threshold {
+            k: 1,
+            children: vec![child],
+        });
+        assert!(matches!(
+            Lsss::<Fr>::from_policy(&deep),
+            Err(PVSSError::PolicyParseError(_))
+        ));
+
+        // Threshold of thresholds, where member 1 owns two rows
+        let policy = Policy::parse("2 of (0 and 1, 2 of (1, 2, 3), 4)").unwrap();
+        let lsss = Lsss::<Fr>::from_policy(&policy).unwrap();
+        assert_eq!(lsss.row_members, vec![0, 1, 1, 2, 3, 4]);
+        let authorized = |rows: &[usize]| lsss.reconstruction_coefficients(rows).is_some();
+        assert!(authorized(&[0, 1, 5]));
+        assert!(authorized(&[2, 3, 5]));
+        assert!(authorized(&[0, 1, 3, 4]));
+        assert!(!authorized(&[0, 2, 3]));
+        assert!(!authorized(&[1, 2, 5]));
+        assert!(!authorized(&[5]));
+    }
+
+    #[test]
+    fn lsss_dealing() {
+        use crate::lsss::*;
+
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..5)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        // 2 of the ops team (0, 1, 2) and 1 of the legal team (3, 4)
+        let policy = Policy::parse("2 of (0, 1, 2) and 1 of (3, 4)").unwrap();
+        let mut lsss_config =
+            LsssConfig::new(pairing_config, committee_pks.clone(), &policy).unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_lsss_with_rng(&lsss_config, &mut rng).unwrap();
+        verify_ciphertext_lsss(&lsss_config, &pvss_ciphertext).unwrap();
+
+        let member_shares = |members: &[usize]| {
+            members
+                .iter()
+                .flat_map(|member| {
+                    decrypt_member_rows(
+                        &lsss_config,
+                        &pvss_ciphertext,
+                        &committee_sks[*member],
+                        *member,
+                    )
+                    .unwrap()
+                })
+                .collect::<Vec<_>>()
+        };
+        for members in [[0, 2, 4], [1, 2, 3]] {
+            let secret = reconstruct_lsss(
+                &lsss_config,
+                Some(&pvss_ciphertext),
+                &member_shares(&members),
+            )
+            .unwrap();
+            assert_eq!(secret, pvss_secrets.h_f_0);
+        }
+        for members in [&[0, 1, 2][..], &[0, 3, 4][..]] {
+            assert!(matches!(
+                reconstruct_lsss(&lsss_config, None, &member_shares(members)),
+                Err(PVSSError::Unauthorized)
+            ));
+        }
+
+        // A share inconsistent with the column commitments is caught
+        let mut tampered = PVSSCiphertext::<Bn254> {
+            f_i: pvss_ciphertext.f_i.clone(),
+            a_i: pvss_ciphertext.a_i.clone(),
+            y_i: pvss_ciphertext.y_i.clone(),
+        };
+        tampered.a_i[1] = tampered.a_i[0];
+        tampered.y_i[1] = tampered.y_i[0];
+        assert!(matches!(
+            verify_ciphertext_lsss(&lsss_config, &tampered),
+            Err(PVSSError::EvaluationsCheckError(_))
+        ));
+
+        // A hand-edited matrix with a short row is rejected before indexing
+        let shares = member_shares(&[0, 2, 4]);
+        lsss_config.lsss.matrix[1].pop();
+        assert!(matches!(
+            verify_ciphertext_lsss(&lsss_config, &pvss_ciphertext),
+            Err(PVSSError::InvalidLsss(_))
+        ));
+        assert!(matches!(
+            reconstruct_lsss(&lsss_config, None, &shares),
+            Err(PVSSError::InvalidLsss(_))
+        ));
+
+        assert!(matches!(
+            LsssConfig::new(
+                PairingConfig::<Bn254>::new(),
+                committee_pks,
+                &Policy::Member(5)
+            ),
+            Err(PVSSError::InvalidParticipantId(5))
+        ));
+    }
+
+    #[test]
+    fn hierarchical_sharing() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..5)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        // 3 shares, at least one from tier 0 (members 1 and 3)
+        let tiers = [1, 0, 1, 0, 1];
+        let lsss_config =
+            LsssConfig::new_hierarchical(pairing_config, committee_pks.clone(), &tiers, &[1, 3])
+                .unwrap();
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_lsss_with_rng(&lsss_config, &mut rng).unwrap();
+        verify_ciphertext_lsss(&lsss_config, &pvss_ciphertext).unwrap();
+
+        let shares = |members: &[usize]| {
+            members
+                .iter()
+                .map(|i| {
+                    (
+                        *i,
+                        decrypt_share(&pvss_ciphertext, &committee_sks[*i], *i).unwrap(),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+        for members in [[1, 3, 0], [3, 2, 4], [1, 0, 4]] {
+            assert_eq!(
+                reconstruct_lsss(&lsss_config, Some(&pvss_ciphertext), &shares(&members)).unwrap(),
+                pvss_secrets.h_f_0
+            );
+        }
+        for members in [&[0, 2, 4][..], &[1, 3][..]] {
+            assert!(matches!(
+                reconstruct_lsss(&lsss_config, None, &shares(members)),
+                Err(PVSSError::Unauthorized)
+            ));
+        }
+
+        for (tiers, thresholds) in [(&[0, 2][..], &[1, 2][..]), (&[0, 1][..], &[2, 2][..])] {
+            assert!(matches!(
+                LsssConfig::new_hierarchical(
+                    PairingConfig::<Bn254>::new(),
+                    committee_pks[..2].to_vec(),
+                    tiers,
+                    thresholds
+                ),
+                Err(PVSSError::InvalidHierarchy(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn packed_sharing() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..8)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let packed_config =
+            PackedPVSSConfig::new(pairing_config, committee_pks.clone(), 2, 3).unwrap();
+        assert_eq!(packed_config.pvss_config.t, 5);
+        let (pvss_ciphertext, packed_secrets) =
+            distribute_packed_secrets_with_rng(&packed_config, &mut rng).unwrap();
+        assert_eq!(pvss_ciphertext.f_i.len(), 5);
+        verify_ciphertext(&packed_config.pvss_config, &pvss_ciphertext).unwrap();
+
+        // Secret 0 sits at x = 0, like an ordinary dealing
+        verify_secret_scalar(
+            &packed_config.pvss_config,
+            &pvss_ciphertext,
+            &packed_secrets.secrets[0],
+        )
+        .unwrap();
+        let g = packed_config.pvss_config.pairing_config.g;
+        let commitments = packed_commitments(&packed_config, &pvss_ciphertext).unwrap();
+        for (commitment, s) in commitments.iter().zip(packed_secrets.secrets.iter()) {
+            assert_eq!(*commitment, g.mul(s.into_repr()).into_affine());
+        }
+
+        let shares = (0..8)
+            .choose_multiple(&mut rng, 5)
+            .into_iter()
+            .map(|i| {
+                (
+                    i,
+                    decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        for j in 0..3 {
+            assert_eq!(
+                reconstruct_packed(&packed_config, Some(&pvss_ciphertext), &shares, j).unwrap(),
+                packed_secrets.h_secrets[j]
+            );
+        }
+        assert!(matches!(
+            reconstruct_packed(&packed_config, None, &shares[..4], 0),
+            Err(PVSSError::NotEnoughShares { have: 4, need: 5 })
+        ));
+        assert!(matches!(
+            reconstruct_packed(&packed_config, None, &shares, 3),
+            Err(PVSSError::InvalidSecretIndex { j: 3, k: 3 })
+        ));
+
+        let secrets = [Fr::from(7u64), Fr::from(11u64)];
+        let small_config =
+            PackedPVSSConfig::new(PairingConfig::<Bn254>::new(), committee_pks.clone(), 1, 2)
+                .unwrap();
+        let (pvss_ciphertext, _) =
+            distribute_given_packed_secrets_with_rng(&small_config, &secrets, &mut rng).unwrap();
+        let shares = (0..3)
+            .map(|i| {
+                (
+                    i,
+                    decrypt_share(&pvss_ciphertext, &committee_sks[i], i).unwrap(),
+                )
+            })
+            .collect::<Vec<_>>();
+        let h = small_config.pvss_config.pairing_config.h;
+        assert_eq!(
+            reconstruct_packed(&small_config, Some(&pvss_ciphertext), &shares, 1).unwrap(),
+            h.mul(secrets[1].into_repr()).into_affine()
+        );
+
+        assert!(matches!(
+            PackedPVSSConfig::new(PairingConfig::<Bn254>::new(), committee_pks, 2, 0),
+            Err(PVSSError::InvalidPacking(0))
+        ));
+    }
+
+    #[test]
+    fn batch_dealing_and_verification() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..5)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config =
+            PVSSConfig::new_weighted(pairing_config, committee_pks, vec![1, 2, 1, 1, 1], 3)
+                .unwrap();
+
+        let mut dealings =
+            distribute_secrets_batch_with_rng(&pvss_config, 4, &mut StdRng::seed_from_u64(7))
+                .unwrap();
+        let again =
+            distribute_secrets_batch_with_rng(&pvss_config, 4, &mut StdRng::seed_from_u64(7))
+                .unwrap();
+        assert_eq!(dealings.len(), 4);
+        for ((ciphertext, secrets), (other, _)) in dealings.iter().zip(again.iter()) {
+            assert_eq!(ciphertext.y_i, other.y_i);
+            verify_ciphertext(&pvss_config, ciphertext).unwrap();
+            let shares = (0..3)
+                .flat_map(|member| {
+                    decrypt_member_shares(&pvss_config, ciphertext, &committee_sks[member], member)
+                        .unwrap()
+                })
+                .collect::<Vec<_>>();
+            assert_eq!(
+                reconstruct_secret(&pvss_config, Some(ciphertext), &shares).unwrap(),
+                secrets.h_f_0
+            );
+        }
+        assert_ne!(dealings[0].1.f_0, dealings[1].1.f_0);
+
+        let mut ciphertexts = dealings.drain(..).map(|(c, _)| c).collect::<Vec<_>>();
+        verify_ciphertexts_batch(&pvss_config, &ciphertexts).unwrap();
+        verify_ciphertexts_batch(&pvss_config, &[]).unwrap();
+
+        ciphertexts[2].y_i.swap(0, 1);
+        assert!(matches!(
+            verify_ciphertexts_batch(&pvss_config, &ciphertexts),
+            Err(PVSSError::RatioIncorrect)
+        ));
+        ciphertexts[2].y_i.swap(0, 1);
+        ciphertexts[3].a_i[4] = ciphertexts[3].a_i[5];
+        assert!(matches!(
+            verify_ciphertexts_batch(&pvss_config, &ciphertexts),
+            Err(PVSSError::EvaluationsCheckError(_))
+        ));
+        ciphertexts[1].f_i.pop();
+        assert!(matches!(
+            verify_ciphertexts_batch(&pvss_config, &ciphertexts),
+            Err(PVSSError::InvalidCiphertextShape { field: "f_i", .. })
+        ));
+    }
+
+    #[test]
+    fn prepared_committee() {
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let (committee_sks, committee_pks): (Vec<_>, Vec<_>) = (0..4)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng))
+            .unzip();
+        let pvss_config =
+            PVSSConfig::new_weighted(pairing_config, committee_pks, vec![2, 1, 1, 1], 3).unwrap();
+        let prepared = PreparedCommittee::new(pvss_config).unwrap();
+        assert_eq!(prepared.slot_pks.len(), 5);
+
+        let (pvss_ciphertext, pvss_secrets) =
+            distribute_secret_prepared_with_rng(&prepared, &mut rng).unwrap();
+        verify_ciphertext_prepared(&prepared, &pvss_ciphertext).unwrap();
+        verify_ciphertext(&prepared.pvss_config, &pvss_ciphertext).unwrap();
+        assert_eq!(
+            pvss_secrets.h_f_0,
+            prepared
+                .pvss_config
+                .pairing_config
+                .h
+                .mul(pvss_secrets.f_0.into_repr())
+                .into_affine()
+        );
+
+        let shares = (0..2)
+            .flat_map(|member| {
+                decrypt_member_shares(
+                    &prepared.pvss_config,
+                    &pvss_ciphertext,
+                    &committee_sks[member],
+                    member,
+                )
+                .unwrap()
+            })
+            .collect::<Vec<_>>();
+        for (i, share) in shares.iter() {
+            verify_share_prepared(&prepared, &pvss_ciphertext, *share, *i).unwrap();
+        }
+        assert!(matches!(
+            verify_share_prepared(&prepared, &pvss_ciphertext, shares[0].1, 1),
+            Err(PVSSError::RatioIncorrect)
+        ));
+        assert_eq!(
+            reconstruct_secret_prepared(&prepared, Some(&pvss_ciphertext), &shares).unwrap(),
+            pvss_secrets.h_f_0
+        );
+
+        let (other_ciphertext, _) = distribute_secret_prepared(&prepared).unwrap();
+        let mut mixed = other_ciphertext;
+        mixed.y_i[4] = pvss_ciphertext.y_i[4];
+        assert!(matches!(
+            verify_ciphertext_prepared(&prepared, &mixed),
+            Err(PVSSError::RatioIncorrect)
+        ));
+        assert!(matches!(
+            reconstruct_secret_prepared(&prepared, Some(&mixed), &shares),
+            Err(PVSSError::SecretMismatch)
+        ));
+    }
+
+    #[test]
+    fn encryption_check_matches_2n_pairings() {
+        use crate::public::encryption_checks::*;
+
+        let mut rng = thread_rng();
+        let pairing_config = PairingConfig::<Bn254>::new();
+        let committee_pks = (0..6)
+            .map(|_| gen_keypair_with_rng(&pairing_config, &mut rng).1)
+            .collect::<Vec<_>>();
+        let pvss_config = PVSSConfig::new(pairing_config, committee_pks, 3).unwrap();
+        let prepared_pks = pvss_config
+            .slot_pks()
+            .into_iter()
+            .map(Into::into)
+            .collect::<Vec<_>>();
+        let (pvss_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+        let (other_ciphertext, _) = distribute_secret_with_rng(&pvss_config, &mut rng).unwrap();
+
+        let mut swapped = pvss_ciphertext.y_i.clone();
+        swapped.swap(1, 2);
+        let mut mixed = pvss_ciphertext.y_i.clone();
+        mixed[5] = other_ciphertext.y_i[5];
+        let mut shifted = pvss_ciphertext.y_i.clone();
+        shifted[0] = shifted[0] + pvss_config.pairing_config.h;
+        let cases = [
+            (pvss_ciphertext.y_i.clone(), true),
+            (swapped, false),
+            (mixed, false),
+            (shifted, false),
+        ];
+        for (y_i, valid) in cases {
+            let alpha = Fr::rand(&mut rng);
+            assert_eq!(
+                verify_encryptions_2n(
+                    &pvss_config.pairing_config,
+                    &pvss_ciphertext.a_i,
+                    &y_i,
+                    &pvss_config.slot_pks(),
+                    alpha
+                )
+                .is_ok(),
+                valid
+            );
+            assert_eq!(
+                verify_encryptions(
+                    &pvss_config.pairing_config,
+                    &pvss_ciphertext.a_i,
+                    &y_i,
+                    &prepared_pks,
+                    alpha
+                )
+                .is_ok(),
+                valid
+            );
+        }
+        assert!(matches!(
+            verify_encryptions(
+                &pvss_config.pairing_config,
+                &pvss_ciphertext.a_i,
+                &pvss_ciphertext.y_i,
+                &prepared_pks[..5],
+                Fr::one()
+            ),
+            Err(PVSSError::InvalidParticipantId(5))
+        ));
     }
 }
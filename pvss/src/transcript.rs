@@ -0,0 +1,201 @@
+use crate::{engine::PVSSEngine, structs::*};
+
+use ark_ec::PairingEngine;
+use ark_ff::{to_bytes, BigInteger, PrimeField};
+use sha3::{Digest, Keccak256};
+
+// NOTE: must match PVSSLib.VERIFY_DISTRIBUTION_DOMAIN
+pub const VERIFY_DISTRIBUTION_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/verify-distribution";
+pub const VERIFY_LSSS_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/verify-lsss";
+pub const CIPHERTEXT_DIGEST_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/ciphertext-digest";
+pub const CONTRIBUTION_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/contribution";
+pub const POSSESSION_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/possession";
+pub const PARTIAL_DECRYPTION_DOMAIN: &[u8] = b"cassiopeia-pvss-v1/partial-decryption";
+
+/// Keccak-256 hash chain over big-endian words, mirroring
+/// `state = keccak256(abi.encodePacked(state, words...))` in Solidity.
+/// Field elements take their full canonical width (32 bytes on BN254).
+pub struct Transcript {
+    state: [u8; 32],
+}
+
+fn field_word<F: PrimeField>(x: &F) -> Vec<u8> {
+    x.into_repr().to_bytes_be()
+}
+
+impl Transcript {
+    pub fn new(domain: &[u8]) -> Self {
+        Transcript {
+            state: Keccak256::digest(domain).into(),
+        }
+    }
+
+    fn absorb<W: AsRef<[u8]>>(&mut self, words: &[W]) {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.state);
+        for word in words {
+            hasher.update(word.as_ref());
+        }
+        self.state = hasher.finalize().into();
+    }
+
+    pub fn append_usize(&mut self, x: usize) {
+        let mut word = [0u8; 32];
+        word[24..].copy_from_slice(&(x as u64).to_be_bytes());
+        self.absorb(&[word]);
+    }
+
+    pub fn append_scalar<F: PrimeField>(&mut self, x: &F) {
+        self.absorb(&[field_word(x)]);
+    }
+
+    pub fn append_g1<E: PVSSEngine>(&mut self, p: &E::G1Affine) {
+        let (x, y) = E::g1_coordinates(p);
+        self.absorb(&[field_word(&x), field_word(&y)]);
+    }
+
+    // NOTE: Important! EIP-197 pairing expects reverse order!
+    pub fn append_g2<E: PVSSEngine>(&mut self, p: &E::G2Affine) {
+        let (x, y) = E::g2_coordinates(p);
+        let [x_c0, x_c1] = E::fqe_to_components(&x);
+        let [y_c0, y_c1] = E::fqe_to_components(&y);
+        self.absorb(&[x_c1, x_c0, y_c1, y_c0].map(|c| field_word(&c)));
+    }
+
+    pub fn append_bytes(&mut self, bytes: &[u8]) {
+        self.append_usize(bytes.len());
+        self.absorb(&[bytes]);
+    }
+
+    // NOTE: GT elements are never checked on-chain, so arkworks' byte order is fine here
+    pub fn append_gt<E: PairingEngine>(&mut self, x: &E::Fqk) {
+        self.absorb(&[to_bytes!(x).expect("writing to a Vec cannot fail")]);
+    }
+
+    pub fn append_ciphertext<E: PVSSEngine>(&mut self, ciphertext: &PVSSCiphertext<E>) {
+        self.append_usize(ciphertext.f_i.len());
+        for f in ciphertext.f_i.iter() {
+            self.append_g1::<E>(f);
+        }
+        self.append_usize(ciphertext.a_i.len());
+        for a in ciphertext.a_i.iter() {
+            self.append_g1::<E>(a);
+        }
+        self.append_usize(ciphertext.y_i.len());
+        for y in ciphertext.y_i.iter() {
+            self.append_g2::<E>(y);
+        }
+    }
+
+    pub fn challenge_scalar<F: PrimeField>(&self) -> F {
+        F::from_be_bytes_mod_order(&self.state)
+    }
+
+    pub fn digest(&self) -> [u8; 32] {
+        self.state
+    }
+}
+
+/// Fiat-Shamir challenge used to batch the checks in `verify_ciphertext`.
+/// Recomputes exactly the `alpha` that `PVSSLib.verifyDistribution` uses on-chain.
+pub fn verify_distribution_challenge<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+) -> E::Fr {
+    let mut transcript = Transcript::new(VERIFY_DISTRIBUTION_DOMAIN);
+    // NOTE: weighted members appear once per slot, as they would on-chain
+    transcript.append_usize(pvss_config.num_slots());
+    transcript.append_usize(pvss_config.t);
+    for pk in pvss_config.slot_pks().iter() {
+        transcript.append_g2::<E>(pk);
+    }
+    transcript.append_ciphertext(ciphertext);
+    transcript.challenge_scalar()
+}
+
+/// Hash identifying a dealing, independent of the committee it was dealt to
+pub fn ciphertext_digest<E: PVSSEngine>(ciphertext: &PVSSCiphertext<E>) -> [u8; 32] {
+    let mut transcript = Transcript::new(CIPHERTEXT_DIGEST_DOMAIN);
+    transcript.append_ciphertext(ciphertext);
+    transcript.digest()
+}
+
+/// Fiat-Shamir challenge for the proof that `d_i = e(u, S_i)` with `e(g, S_i) = e(a_i, h)`
+pub fn partial_decryption_challenge<E: PVSSEngine>(
+    i: usize,
+    a_i: &E::G1Affine,
+    u: &E::G1Affine,
+    d_i: &E::Fqk,
+    t_1: &E::Fqk,
+    t_2: &E::Fqk,
+) -> E::Fr {
+    let mut transcript = Transcript::new(PARTIAL_DECRYPTION_DOMAIN);
+    transcript.append_usize(i);
+    transcript.append_g1::<E>(a_i);
+    transcript.append_g1::<E>(u);
+    transcript.append_gt::<E>(d_i);
+    transcript.append_gt::<E>(t_1);
+    transcript.append_gt::<E>(t_2);
+    transcript.challenge_scalar()
+}
+
+/// Fiat-Shamir challenge for a dealer's signature of knowledge of `f_0` and its `sk`.
+/// Binds the DKG session, the committee and the dealt ciphertext, so proofs cannot
+/// be replayed in another run.
+pub fn contribution_challenge<E: PVSSEngine>(
+    pvss_config: &PVSSConfig<E>,
+    session_id: &[u8],
+    proof: &ContributionProof<E>,
+    r: &E::G1Affine,
+    r_sk: &E::G2Affine,
+) -> E::Fr {
+    let mut transcript = Transcript::new(CONTRIBUTION_DOMAIN);
+    transcript.append_bytes(session_id);
+    transcript.append_usize(pvss_config.t);
+    transcript.append_usize(pvss_config.committee_pks.len());
+    for pk in pvss_config.committee_pks.iter() {
+        transcript.append_g2::<E>(pk);
+    }
+    transcript.append_usize(proof.dealer);
+    transcript.append_g2::<E>(&proof.pk);
+    transcript.append_bytes(&proof.ciphertext_hash);
+    transcript.append_g1::<E>(&proof.commitment);
+    transcript.append_g1::<E>(r);
+    transcript.append_g2::<E>(r_sk);
+    transcript.challenge_scalar()
+}
+
+/// Fiat-Shamir challenge for a committee member's proof of possession of `sk`
+pub fn possession_challenge<E: PVSSEngine>(pk: &E::G2Affine, r: &E::G2Affine) -> E::Fr {
+    let mut transcript = Transcript::new(POSSESSION_DOMAIN);
+    transcript.append_g2::<E>(pk);
+    transcript.append_g2::<E>(r);
+    transcript.challenge_scalar()
+}
+
+/// Fiat-Shamir challenge used to batch the checks in `verify_ciphertext_lsss`
+pub fn verify_lsss_challenge<E: PVSSEngine>(
+    lsss_config: &LsssConfig<E>,
+    ciphertext: &PVSSCiphertext<E>,
+) -> E::Fr {
+    let mut transcript = Transcript::new(VERIFY_LSSS_DOMAIN);
+    transcript.append_usize(lsss_config.lsss.num_rows());
+    transcript.append_usize(lsss_config.lsss.num_cols());
+    for (row, member) in lsss_config
+        .lsss
+        .matrix
+        .iter()
+        .zip(lsss_config.lsss.row_members.iter())
+    {
+        transcript.append_usize(*member);
+        for m in row.iter() {
+            transcript.append_scalar(m);
+        }
+    }
+    transcript.append_usize(lsss_config.committee_pks.len());
+    for pk in lsss_config.committee_pks.iter() {
+        transcript.append_g2::<E>(pk);
+    }
+    transcript.append_ciphertext(ciphertext);
+    transcript.challenge_scalar()
+}
@@ -1,4 +1,7 @@
 import { execFileSync } from "child_process";
+import { unlinkSync, writeFileSync } from "fs";
+import { tmpdir } from "os";
+import { join } from "path";
 import { expect, util } from "chai";
 import { shuffled, randomBytes, hexlify } from "ethers/lib/utils";
 import { PVSS_BIN, combineShares, decryptShare } from "./cassiopeia_lib";
@@ -56,4 +59,31 @@ describe("PVSS CLI", () => {
 		];
 		expect(combineShares(newDecryptedShares)).to.not.deep.equal(pvssOutput.secrets.h_f_0);
 	});
+
+  it("Should deal short and uppercase hex secrets and reject values outside the field", () => {
+    const allKeys = JSON.parse(execFileSync(PVSS_BIN, ["gen-keys", "3"]).toString());
+    const pks = JSON.stringify(allKeys.pks);
+    const dealStdin = (secret: string) =>
+      JSON.parse(
+        execFileSync(PVSS_BIN, ["deal-secret", "2", "--secret-stdin"], {
+          input: `${pks}\n${secret}\n`,
+        }).toString()
+      );
+    expect(BigInt(dealStdin("0x05").secrets.f_0)).to.equal(5n);
+
+    const secretFile = join(tmpdir(), `secret-${process.pid}.txt`);
+    writeFileSync(secretFile, "0x" + "0".repeat(62) + "0A\n");
+    const fromFile = JSON.parse(
+      execFileSync(PVSS_BIN, ["deal-secret", "2", "--secret-file", secretFile], {
+        input: pks,
+      }).toString()
+    );
+    unlinkSync(secretFile);
+    expect(BigInt(fromFile.secrets.f_0)).to.equal(10n);
+
+    // The BN254 scalar field modulus r
+    expect(() =>
+      dealStdin("0x30644E72E131A029B85045B68181585D2833E84879B9709143E1F593F0000001")
+    ).to.throw(/SecretOutOfRange/);
+  });
 });
@@ -0,0 +1,58 @@
+import { loadFixture } from "@nomicfoundation/hardhat-network-helpers";
+import { ethers } from "hardhat";
+import { expect } from "chai";
+import { BigNumber } from "ethers";
+import { readFileSync } from "fs";
+import { join } from "path";
+
+// NOTE: pvss/src/tests.rs asserts the same challenge from verify_distribution_challenge
+const vector = JSON.parse(
+  readFileSync(
+    join(__dirname, "vectors/verify_distribution_challenge.json")
+  ).toString()
+);
+
+describe("PVSSLib", () => {
+  const deployFixture = async () => {
+    const PairingLib = await ethers
+      .getContractFactory("PairingLib")
+      .then((factory) => factory.deploy());
+    const PVSSLib = await ethers
+      .getContractFactory("PVSSLib", {
+        libraries: {
+          PairingLib: PairingLib.address,
+        },
+      })
+      .then((factory) => factory.deploy());
+    return { PVSSLib };
+  };
+
+  it("Should derive the same challenge as the Rust transcript", async () => {
+    const { PVSSLib } = await loadFixture(deployFixture);
+    const challenge = await PVSSLib.genChallenge(
+      vector.n,
+      vector.t,
+      vector.pks,
+      vector.ciphertext
+    );
+    expect(challenge).to.equal(BigNumber.from(vector.challenge));
+    await PVSSLib.verifyDistribution(
+      vector.n,
+      vector.t,
+      vector.pks,
+      vector.ciphertext
+    );
+  });
+
+  it("Should reject a committee whose size is not n", async () => {
+    const { PVSSLib } = await loadFixture(deployFixture);
+    await expect(
+      PVSSLib.genChallenge(
+        vector.n,
+        vector.t,
+        vector.pks.slice(1),
+        vector.ciphertext
+      )
+    ).to.be.revertedWith("pks should have length n");
+  });
+});